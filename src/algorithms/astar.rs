@@ -3,10 +3,61 @@ use std::collections::BinaryHeap;
 use std::cmp::Reverse;
 use crate::grid::Grid;
 use crate::stats::Stats;
-use super::reconstruct_path;
+use crate::maze::trace::{Event, Trace};
+use super::{now, open_cell, reconstruct_path, route_to, SearchData};
 
-pub fn run(grid: &mut Grid) -> Stats {
-    let start_time = macroquad::time::get_time();
+#[derive(Clone, Copy, PartialEq)]
+pub enum Heuristic {
+    Manhattan,
+    Euclid,
+    Chebyshev,
+}
+
+impl Heuristic {
+    pub const ALL: [Heuristic; 3] = [Heuristic::Manhattan, Heuristic::Euclid, Heuristic::Chebyshev];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heuristic::Manhattan => "Manhattan",
+            Heuristic::Euclid    => "Euclid",
+            Heuristic::Chebyshev => "Chebyshev",
+        }
+    }
+}
+
+/// How A* estimates the cost left. All three heuristics underestimate on a
+/// 4-connected grid; a weight above 1 trades that guarantee for speed.
+#[derive(Clone, Copy)]
+pub struct Params {
+    pub heuristic: Heuristic,
+    pub weight: f32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { heuristic: Heuristic::Manhattan, weight: 1.0 }
+    }
+}
+
+impl Params {
+    /// Largest weight accepted; past this A* is plain greedy best-first.
+    pub const MAX_WEIGHT: f32 = 10.0;
+
+    /// Weighted estimate from `idx` to `end`, rounded down.
+    fn estimate(&self, idx: usize, end: usize, width: usize) -> u32 {
+        let dr = (idx / width).abs_diff(end / width) as f32;
+        let dc = (idx % width).abs_diff(end % width) as f32;
+        let distance = match self.heuristic {
+            Heuristic::Manhattan => dr + dc,
+            Heuristic::Euclid    => (dr * dr + dc * dc).sqrt(),
+            Heuristic::Chebyshev => dr.max(dc),
+        };
+        (distance * self.weight) as u32
+    }
+}
+
+pub fn run(grid: &mut Grid, trace: &mut Trace, data: &mut SearchData, params: &Params) -> Stats {
+    let start_time = now();
 
     let mut start_idx = None;
     let mut end_idx = None;
@@ -18,6 +69,7 @@ pub fn run(grid: &mut Grid) -> Stats {
     let (Some(start), Some(end)) = (start_idx, end_idx) else {
         return Stats::default();
     };
+    trace.begin(grid);
 
     let size = grid.width * grid.height;
     let mut g_cost = vec![u32::MAX; size];
@@ -26,37 +78,55 @@ pub fn run(grid: &mut Grid) -> Stats {
     let mut heap = BinaryHeap::new();
 
     g_cost[start] = 0;
-    heap.push(Reverse((manhattan(start, end, grid.width), start)));
+    data.discovered[start] = 0;
+    data.h[start] = params.estimate(start, end, grid.width);
+    heap.push(Reverse((data.h[start], start)));
+    let mut max_open = 1;
 
     'search: while let Some(Reverse((_f, current))) = heap.pop() {
         if current != start {
-            let expected_f = g_cost[current] + manhattan(current, end, grid.width);
+            let expected_f = g_cost[current] + data.h[current];
             if _f > expected_f { continue; }
         }
+        data.order[current] = nodes_explored;
         nodes_explored += 1;
         if current == end { break 'search; }
+        trace.push(Event::Head(current));
         let cell = grid.cell_mut(current);
-        if !cell.is_start { cell.is_visited = true; }
+        if !cell.is_start {
+            cell.is_visited = true;
+            cell.is_open = false;
+            trace.push(Event::Visit(current));
+        }
         for neighbor in grid.neighbors(current) {
             let neighbor_cost = grid.cell(neighbor).cost as u32;
             let tentative_g = g_cost[current] + neighbor_cost;
             if tentative_g < g_cost[neighbor] {
                 g_cost[neighbor] = tentative_g;
+                if data.discovered[neighbor] == u32::MAX { data.discovered[neighbor] = data.order[current]; }
                 parent[neighbor] = current;
-                let f = tentative_g + manhattan(neighbor, end, grid.width);
+                data.h[neighbor] = params.estimate(neighbor, end, grid.width);
+                let f = tentative_g + data.h[neighbor];
+                open_cell(grid, trace, neighbor);
                 heap.push(Reverse((f, neighbor)));
             }
         }
+        max_open = max_open.max(heap.len());
     }
 
-    let path_length = reconstruct_path(grid, &parent, start, end);
-    let execution_ms = (macroquad::time::get_time() - start_time) * 1000.0;
-    Stats { nodes_explored, path_length, execution_ms, path_found: path_length > 0 }
+    let path_length = reconstruct_path(grid, trace, &parent, start, end);
+    data.route = route_to(&parent, start, end);
+    data.parent = parent;
+    let execution_ms = (now() - start_time) * 1000.0;
+    data.g = g_cost;
+    let peak_bytes = size * (size_of::<u32>() + size_of::<usize>()) + max_open * size_of::<(u32, usize)>();
+    Stats {
+        nodes_explored,
+        path_length,
+        execution_ms,
+        path_found: path_length > 0,
+        max_open: max_open as u32,
+        peak_bytes,
+        ..Stats::default()
+    }
 }
-
-fn manhattan(idx: usize, end: usize, width: usize) -> u32 {
-    let (r1, c1) = (idx / width, idx % width);
-    let (r2, c2) = (end / width, end % width);
-    (r1 as i32 - r2 as i32).unsigned_abs()
-        + (c1 as i32 - c2 as i32).unsigned_abs()
-}
\ No newline at end of file
@@ -0,0 +1,70 @@
+// src/algorithms/dead_end_filling.rs
+use std::collections::VecDeque;
+use crate::grid::Grid;
+use crate::stats::Stats;
+use crate::maze::trace::{Event, Trace};
+use super::{mark_route, now, route_to, SearchData};
+
+/// Fills every dead end back to the nearest junction until none are left.
+/// What stays open is the solution (plus any loops), and the route is read
+/// off it with a BFS. Each filled cell counts as a step, in fill order.
+pub fn run(grid: &mut Grid, trace: &mut Trace, data: &mut SearchData) -> Stats {
+    let start_time = now();
+    let (Some(start), Some(end)) = (grid.start(), grid.end()) else {
+        return Stats::default();
+    };
+    trace.begin(grid);
+
+    let size = grid.width * grid.height;
+    let mut degree: Vec<usize> = (0..size).map(|i| grid.neighbors(i).len()).collect();
+    let mut filled = vec![false; size];
+    let mut stack: Vec<usize> = (0..size)
+        .filter(|&i| !grid.cell(i).is_wall && degree[i] <= 1)
+        .collect();
+    let mut steps = 0;
+    let mut max_stack = stack.len();
+
+    while let Some(current) = stack.pop() {
+        if filled[current] || current == start || current == end || degree[current] > 1 { continue; }
+        filled[current] = true;
+        data.order[current] = steps;
+        steps += 1;
+        grid.cell_mut(current).is_visited = true;
+        trace.push(Event::Head(current));
+        trace.push(Event::Visit(current));
+        for neighbor in grid.neighbors(current) {
+            if filled[neighbor] { continue; }
+            degree[neighbor] -= 1;
+            if degree[neighbor] <= 1 { stack.push(neighbor); }
+        }
+        max_stack = max_stack.max(stack.len());
+    }
+
+    // Shortest route through what is left.
+    let mut parent = vec![usize::MAX; size];
+    let mut queue = VecDeque::from([start]);
+    parent[start] = start;
+    while let Some(current) = queue.pop_front() {
+        if current == end { break; }
+        for neighbor in grid.neighbors(current) {
+            if !filled[neighbor] && parent[neighbor] == usize::MAX {
+                parent[neighbor] = current;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    let route = route_to(&parent, start, end);
+    let path_length = mark_route(grid, trace, &route);
+    let path_found = !route.is_empty();
+    data.route = route;
+    let execution_ms = (now() - start_time) * 1000.0;
+    Stats {
+        nodes_explored: steps,
+        path_length,
+        execution_ms,
+        path_found,
+        steps,
+        peak_bytes: size * (2 * size_of::<usize>() + size_of::<bool>()) + max_stack * size_of::<usize>(),
+        ..Stats::default()
+    }
+}
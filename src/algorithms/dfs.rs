@@ -1,10 +1,11 @@
 // src/algorithms/dfs.rs
 use crate::grid::Grid;
 use crate::stats::Stats;
-use super::reconstruct_path;
+use crate::maze::trace::{Event, Trace};
+use super::{now, open_cell, reconstruct_path, route_to, SearchData};
 
-pub fn run(grid: &mut Grid) -> Stats {
-    let start_time = macroquad::time::get_time();
+pub fn run(grid: &mut Grid, trace: &mut Trace, data: &mut SearchData) -> Stats {
+    let start_time = now();
 
     let mut start_idx = None;
     let mut end_idx = None;
@@ -16,6 +17,7 @@ pub fn run(grid: &mut Grid) -> Stats {
     let (Some(start), Some(end)) = (start_idx, end_idx) else {
         return Stats::default();
     };
+    trace.begin(grid);
 
     let size = grid.width * grid.height;
     let mut visited = vec![false; size];
@@ -24,23 +26,46 @@ pub fn run(grid: &mut Grid) -> Stats {
     let mut nodes_explored: u32 = 0;
 
     stack.push(start);
+    data.discovered[start] = 0;
+    let mut max_open = 1;
 
     'search: while let Some(current) = stack.pop() {
         if visited[current] { continue; }
         visited[current] = true;
+        data.g[current] = if current == start { 0 } else { data.g[parent[current]] + 1 };
+        data.order[current] = nodes_explored;
         nodes_explored += 1;
         if current == end { break 'search; }
+        trace.push(Event::Head(current));
         let cell = grid.cell_mut(current);
-        if !cell.is_start { cell.is_visited = true; }
+        if !cell.is_start {
+            cell.is_visited = true;
+            cell.is_open = false;
+            trace.push(Event::Visit(current));
+        }
         for neighbor in grid.neighbors(current) {
             if !visited[neighbor] {
                 parent[neighbor] = current;
+                if data.discovered[neighbor] == u32::MAX { data.discovered[neighbor] = data.order[current]; }
+                open_cell(grid, trace, neighbor);
                 stack.push(neighbor);
             }
         }
+        max_open = max_open.max(stack.len());
     }
 
-    let path_length = reconstruct_path(grid, &parent, start, end);
-    let execution_ms = (macroquad::time::get_time() - start_time) * 1000.0;
-    Stats { nodes_explored, path_length, execution_ms, path_found: path_length > 0 }
+    let path_length = reconstruct_path(grid, trace, &parent, start, end);
+    data.route = route_to(&parent, start, end);
+    data.parent = parent;
+    let execution_ms = (now() - start_time) * 1000.0;
+    let peak_bytes = size * (size_of::<bool>() + size_of::<usize>()) + max_open * size_of::<usize>();
+    Stats {
+        nodes_explored,
+        path_length,
+        execution_ms,
+        path_found: path_length > 0,
+        max_open: max_open as u32,
+        peak_bytes,
+        ..Stats::default()
+    }
 }
\ No newline at end of file
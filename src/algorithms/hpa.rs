@@ -0,0 +1,576 @@
+// src/algorithms/hpa.rs
+use std::collections::{BinaryHeap, HashMap};
+use std::cmp::Reverse;
+use crate::grid::{Cell, Grid};
+use crate::stats::Stats;
+use super::{now, SearchData};
+
+pub const CLUSTER_SIZE: usize = 16;
+
+// Border runs shorter than this get a single entrance in the middle,
+// longer runs get one at each end (Botea et al.).
+const MAX_ENTRANCE_RUN: usize = 6;
+
+#[derive(Clone, Copy)]
+pub struct Bounds {
+    pub r0: usize,
+    pub c0: usize,
+    pub r1: usize,
+    pub c1: usize,
+}
+
+impl Bounds {
+    fn contains(&self, row: usize, col: usize) -> bool {
+        row >= self.r0 && row < self.r1 && col >= self.c0 && col < self.c1
+    }
+
+    fn width(&self) -> usize { self.c1 - self.c0 }
+
+    fn local(&self, idx: usize, grid_width: usize) -> usize {
+        let (row, col) = (idx / grid_width, idx % grid_width);
+        (row - self.r0) * self.width() + (col - self.c0)
+    }
+}
+
+/// Cluster decomposition and abstract entrance graph for HPA*.
+/// Built lazily on the first query and patched per cluster afterwards.
+pub struct Hierarchy {
+    pub cluster_size: usize,
+    pub cols: usize,
+    pub rows: usize,
+    width: usize,
+    height: usize,
+    snapshot: Vec<u16>,
+    east: Vec<Vec<(usize, usize)>>,
+    south: Vec<Vec<(usize, usize)>>,
+    intra: Vec<Vec<(usize, usize, u32)>>,
+    graph: HashMap<usize, Vec<(usize, u32)>>,
+    pub abstract_path: Vec<usize>,
+    pub clusters_rebuilt: usize,
+}
+
+struct LocalSearch {
+    dist: Vec<u32>,
+    parent: Vec<usize>,
+    explored: u32,
+}
+
+impl Hierarchy {
+    pub fn new(cluster_size: usize) -> Self {
+        Self {
+            cluster_size,
+            cols: 0,
+            rows: 0,
+            width: 0,
+            height: 0,
+            snapshot: Vec::new(),
+            east: Vec::new(),
+            south: Vec::new(),
+            intra: Vec::new(),
+            graph: HashMap::new(),
+            abstract_path: Vec::new(),
+            clusters_rebuilt: 0,
+        }
+    }
+
+    /// Brings the abstraction in sync with `grid`. Only clusters whose walls or
+    /// terrain changed since the last call (plus their direct neighbours, whose
+    /// entrances may have moved) are recomputed. Returns the number rebuilt.
+    /// The cells are only compared when the grid says it was edited.
+    pub fn update(&mut self, grid: &mut Grid) -> usize {
+        let mut dirty = Vec::new();
+        let edited = grid.take_edited();
+
+        if grid.width != self.width || grid.height != self.height {
+            self.width = grid.width;
+            self.height = grid.height;
+            self.cols = grid.width.div_ceil(self.cluster_size);
+            self.rows = grid.height.div_ceil(self.cluster_size);
+            let count = self.cols * self.rows;
+            self.snapshot = (0..grid.width * grid.height).map(|i| weight(grid.cell(i))).collect();
+            self.east = vec![Vec::new(); count];
+            self.south = vec![Vec::new(); count];
+            self.intra = vec![Vec::new(); count];
+            dirty.extend(0..count);
+        } else if edited {
+            let mut flagged = vec![false; self.cols * self.rows];
+            for i in 0..(grid.width * grid.height) {
+                let w = weight(grid.cell(i));
+                if self.snapshot[i] != w {
+                    self.snapshot[i] = w;
+                    let c = self.cluster_of(i);
+                    if !flagged[c] {
+                        flagged[c] = true;
+                        dirty.push(c);
+                    }
+                }
+            }
+        }
+
+        if dirty.is_empty() {
+            self.clusters_rebuilt = 0;
+            return 0;
+        }
+
+        let mut touched = vec![false; self.cols * self.rows];
+        for &c in &dirty {
+            self.build_borders(grid, c);
+            if let Some(w) = self.west_of(c)  { self.build_borders(grid, w); }
+            if let Some(n) = self.north_of(c) { self.build_borders(grid, n); }
+            touched[c] = true;
+            for n in self.adjacent(c) { touched[n] = true; }
+        }
+
+        let mut rebuilt = 0;
+        for (c, &hit) in touched.iter().enumerate() {
+            if hit {
+                self.build_intra(grid, c);
+                rebuilt += 1;
+            }
+        }
+
+        self.rebuild_graph();
+        self.clusters_rebuilt = rebuilt;
+        rebuilt
+    }
+
+    pub fn cluster_of(&self, idx: usize) -> usize {
+        let row = idx / self.width;
+        let col = idx % self.width;
+        (row / self.cluster_size) * self.cols + col / self.cluster_size
+    }
+
+    pub fn bounds(&self, c: usize) -> Bounds {
+        let r0 = (c / self.cols) * self.cluster_size;
+        let c0 = (c % self.cols) * self.cluster_size;
+        Bounds {
+            r0,
+            c0,
+            r1: (r0 + self.cluster_size).min(self.height),
+            c1: (c0 + self.cluster_size).min(self.width),
+        }
+    }
+
+    /// Every cell that acts as an entrance node in the abstract graph.
+    pub fn entrances(&self) -> impl Iterator<Item = &usize> {
+        self.graph.keys()
+    }
+
+    fn west_of(&self, c: usize) -> Option<usize> {
+        if !c.is_multiple_of(self.cols) { Some(c - 1) } else { None }
+    }
+
+    fn north_of(&self, c: usize) -> Option<usize> {
+        if c >= self.cols { Some(c - self.cols) } else { None }
+    }
+
+    fn adjacent(&self, c: usize) -> Vec<usize> {
+        let mut result = Vec::with_capacity(4);
+        if let Some(w) = self.west_of(c)  { result.push(w); }
+        if let Some(n) = self.north_of(c) { result.push(n); }
+        if c % self.cols + 1 < self.cols  { result.push(c + 1); }
+        if c + self.cols < self.cols * self.rows { result.push(c + self.cols); }
+        result
+    }
+
+    fn build_borders(&mut self, grid: &Grid, c: usize) {
+        let b = self.bounds(c);
+
+        self.east[c].clear();
+        if b.c1 < self.width {
+            let pairs = (b.r0..b.r1).map(|r| (grid.idx(r, b.c1 - 1), grid.idx(r, b.c1))).collect();
+            self.east[c] = entrances_along(grid, pairs);
+        }
+
+        self.south[c].clear();
+        if b.r1 < self.height {
+            let pairs = (b.c0..b.c1).map(|col| (grid.idx(b.r1 - 1, col), grid.idx(b.r1, col))).collect();
+            self.south[c] = entrances_along(grid, pairs);
+        }
+    }
+
+    fn nodes_in(&self, c: usize) -> Vec<usize> {
+        let mut nodes: Vec<usize> = Vec::new();
+        nodes.extend(self.east[c].iter().map(|&(a, _)| a));
+        nodes.extend(self.south[c].iter().map(|&(a, _)| a));
+        if let Some(w) = self.west_of(c)  { nodes.extend(self.east[w].iter().map(|&(_, b)| b)); }
+        if let Some(n) = self.north_of(c) { nodes.extend(self.south[n].iter().map(|&(_, b)| b)); }
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+
+    fn build_intra(&mut self, grid: &Grid, c: usize) {
+        let b = self.bounds(c);
+        let nodes = self.nodes_in(c);
+        let mut edges = Vec::new();
+
+        for &from in &nodes {
+            let search = local_search(grid, from, b, None);
+            for &to in &nodes {
+                if to == from { continue; }
+                let d = search.dist[b.local(to, grid.width)];
+                if d != u32::MAX { edges.push((from, to, d)); }
+            }
+        }
+
+        self.intra[c] = edges;
+    }
+
+    fn rebuild_graph(&mut self) {
+        self.graph.clear();
+        for c in 0..self.intra.len() {
+            for &(a, b) in self.east[c].iter().chain(self.south[c].iter()) {
+                self.graph.entry(a).or_default().push((b, self.snapshot[b] as u32));
+                self.graph.entry(b).or_default().push((a, self.snapshot[a] as u32));
+            }
+            for &(from, to, cost) in &self.intra[c] {
+                self.graph.entry(from).or_default().push((to, cost));
+            }
+        }
+    }
+}
+
+pub fn run(grid: &mut Grid, data: &mut SearchData, hierarchy: &mut Hierarchy) -> Stats {
+    let prep_time = now();
+    hierarchy.update(grid);
+    let preprocess_ms = (now() - prep_time) * 1000.0;
+
+    let start_time = now();
+    hierarchy.abstract_path.clear();
+
+    let mut start_idx = None;
+    let mut end_idx = None;
+    for i in 0..(grid.width * grid.height) {
+        if grid.cell(i).is_start { start_idx = Some(i); }
+        if grid.cell(i).is_end   { end_idx = Some(i); }
+    }
+
+    let (Some(start), Some(end)) = (start_idx, end_idx) else {
+        return Stats { preprocess_ms, ..Stats::default() };
+    };
+
+    let mut nodes_explored: u32 = 0;
+    let mut visited = Vec::new();
+    let sc = hierarchy.cluster_of(start);
+    let ec = hierarchy.cluster_of(end);
+
+    // Same cluster: try the direct local route before going abstract.
+    let mut path = None;
+    if sc == ec {
+        let b = hierarchy.bounds(sc);
+        let search = local_search(grid, start, b, Some(end));
+        nodes_explored += search.explored;
+        path = trace_local(&search, b, grid.width, start, end);
+    }
+
+    if path.is_none() {
+        let sb = hierarchy.bounds(sc);
+        let eb = hierarchy.bounds(ec);
+        let from_start = local_search(grid, start, sb, None);
+        let from_end = local_search(grid, end, eb, None);
+        nodes_explored += from_start.explored + from_end.explored;
+
+        // Temporary edges into and out of the abstract graph.
+        let mut start_edges = Vec::new();
+        for n in hierarchy.nodes_in(sc) {
+            let d = from_start.dist[sb.local(n, grid.width)];
+            if d != u32::MAX { start_edges.push((n, d)); }
+        }
+        let mut end_edges: HashMap<usize, u32> = HashMap::new();
+        for n in hierarchy.nodes_in(ec) {
+            let d = from_end.dist[eb.local(n, grid.width)];
+            if d != u32::MAX {
+                // Costs are paid on entry, so reverse the direction of travel.
+                let d = d + grid.cell(end).cost as u32 - grid.cell(n).cost as u32;
+                end_edges.insert(n, d);
+            }
+        }
+
+        if let Some((route, expanded)) = abstract_search(hierarchy, grid.width, start, end, &start_edges, &end_edges) {
+            nodes_explored += expanded.len() as u32;
+            visited.extend(expanded);
+            let mut cells = vec![start];
+            for pair in route.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                let ca = hierarchy.cluster_of(a);
+                if ca == hierarchy.cluster_of(b) {
+                    let bounds = hierarchy.bounds(ca);
+                    let search = local_search(grid, a, bounds, Some(b));
+                    nodes_explored += search.explored;
+                    match trace_local(&search, bounds, grid.width, a, b) {
+                        Some(segment) => cells.extend(&segment[1..]),
+                        None => { cells.clear(); break; }
+                    }
+                } else {
+                    cells.push(b);
+                }
+            }
+            if !cells.is_empty() { path = Some(cells); }
+            hierarchy.abstract_path = route;
+        }
+    }
+
+    for idx in visited {
+        let cell = grid.cell_mut(idx);
+        if !cell.is_start && !cell.is_end { cell.is_visited = true; }
+    }
+
+    let mut path_length = 0;
+    if let Some(cells) = path {
+        for &idx in &cells[1..] {
+            let cell = grid.cell_mut(idx);
+            if !cell.is_end { cell.is_path = true; }
+        }
+        path_length = (cells.len() - 1) as u32;
+        data.route = cells;
+    }
+
+    let execution_ms = (now() - start_time) * 1000.0;
+    Stats { nodes_explored, path_length, execution_ms, path_found: path_length > 0, preprocess_ms, ..Stats::default() }
+}
+
+fn weight(cell: &Cell) -> u16 {
+    if cell.is_wall { 0 } else { cell.cost }
+}
+
+fn entrances_along(grid: &Grid, pairs: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    let mut result = Vec::new();
+    let mut run_start = None;
+
+    for i in 0..=pairs.len() {
+        let open = i < pairs.len()
+            && !grid.cell(pairs[i].0).is_wall
+            && !grid.cell(pairs[i].1).is_wall;
+        match (open, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(s)) => {
+                if i - s < MAX_ENTRANCE_RUN {
+                    result.push(pairs[(s + i - 1) / 2]);
+                } else {
+                    result.push(pairs[s]);
+                    result.push(pairs[i - 1]);
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    result
+}
+
+fn local_search(grid: &Grid, from: usize, b: Bounds, target: Option<usize>) -> LocalSearch {
+    let size = (b.r1 - b.r0) * b.width();
+    let mut dist = vec![u32::MAX; size];
+    let mut parent = vec![usize::MAX; size];
+    let mut explored = 0;
+    let mut heap = BinaryHeap::new();
+
+    dist[b.local(from, grid.width)] = 0;
+    heap.push(Reverse((0u32, from)));
+
+    while let Some(Reverse((cost, current))) = heap.pop() {
+        if cost > dist[b.local(current, grid.width)] { continue; }
+        explored += 1;
+        if Some(current) == target { break; }
+        for neighbor in grid.neighbors(current) {
+            if !b.contains(neighbor / grid.width, neighbor % grid.width) { continue; }
+            let next_cost = cost + grid.cell(neighbor).cost as u32;
+            let local = b.local(neighbor, grid.width);
+            if next_cost < dist[local] {
+                dist[local] = next_cost;
+                parent[local] = current;
+                heap.push(Reverse((next_cost, neighbor)));
+            }
+        }
+    }
+
+    LocalSearch { dist, parent, explored }
+}
+
+fn trace_local(search: &LocalSearch, b: Bounds, width: usize, from: usize, to: usize) -> Option<Vec<usize>> {
+    if search.dist[b.local(to, width)] == u32::MAX { return None; }
+    let mut cells = vec![to];
+    let mut current = to;
+    while current != from {
+        current = search.parent[b.local(current, width)];
+        cells.push(current);
+    }
+    cells.reverse();
+    Some(cells)
+}
+
+fn abstract_search(
+    hierarchy: &Hierarchy,
+    width: usize,
+    start: usize,
+    end: usize,
+    start_edges: &[(usize, u32)],
+    end_edges: &HashMap<usize, u32>,
+) -> Option<(Vec<usize>, Vec<usize>)> {
+    let mut g_cost: HashMap<usize, u32> = HashMap::new();
+    let mut parent: HashMap<usize, usize> = HashMap::new();
+    let mut expanded = Vec::new();
+    let mut heap = BinaryHeap::new();
+
+    g_cost.insert(start, 0);
+    heap.push(Reverse((manhattan(start, end, width), start)));
+
+    while let Some(Reverse((f, current))) = heap.pop() {
+        let g = g_cost[&current];
+        if f > g + manhattan(current, end, width) { continue; }
+        if current == end {
+            let mut route = vec![end];
+            let mut node = end;
+            while let Some(&p) = parent.get(&node) {
+                route.push(p);
+                node = p;
+            }
+            route.reverse();
+            return Some((route, expanded));
+        }
+        expanded.push(current);
+
+        let mut edges = hierarchy.graph.get(&current).cloned().unwrap_or_default();
+        if current == start { edges.extend_from_slice(start_edges); }
+        if let Some(&d) = end_edges.get(&current) { edges.push((end, d)); }
+
+        for (neighbor, cost) in edges {
+            let tentative_g = g + cost;
+            if tentative_g < *g_cost.get(&neighbor).unwrap_or(&u32::MAX) {
+                g_cost.insert(neighbor, tentative_g);
+                parent.insert(neighbor, current);
+                heap.push(Reverse((tentative_g + manhattan(neighbor, end, width), neighbor)));
+            }
+        }
+    }
+
+    None
+}
+
+fn manhattan(idx: usize, end: usize, width: usize) -> u32 {
+    let (r1, c1) = (idx / width, idx % width);
+    let (r2, c2) = (end / width, end % width);
+    (r1 as i32 - r2 as i32).unsigned_abs()
+        + (c1 as i32 - c2 as i32).unsigned_abs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::dijkstra;
+    use crate::grid::{COST_MUD, COST_WATER};
+    use crate::maze::{noise, trace::Trace, Generator, Settings};
+
+    /// HPA* and Dijkstra on the same grid: (HPA* cost, optimal cost), with
+    /// `None` for no path. Also checks the HPA* route is a real walk.
+    fn costs(grid: &Grid) -> (Option<u32>, Option<u32>) {
+        let size = grid.width * grid.height;
+        let mut exact = grid.clone();
+        let mut data = SearchData::new(size);
+        let optimal = dijkstra::run(&mut exact, &mut Trace::off(), &mut data).path_found
+            .then(|| data.g[grid.end().unwrap()]);
+
+        let mut abstracted = grid.clone();
+        let mut data = SearchData::new(size);
+        let stats = run(&mut abstracted, &mut data, &mut Hierarchy::new(CLUSTER_SIZE));
+        if !stats.path_found { return (None, optimal); }
+        let route = &data.route;
+        assert_eq!(route.first().copied(), grid.start());
+        assert_eq!(route.last().copied(), grid.end());
+        for pair in route.windows(2) {
+            assert!(grid.neighbors(pair[0]).contains(&pair[1]), "{} -> {} is not a step", pair[0], pair[1]);
+        }
+        (Some(route[1..].iter().map(|&i| grid.cell(i).cost as u32).sum()), optimal)
+    }
+
+    fn with_corners(mut grid: Grid) -> Grid {
+        let (start, end) = (grid.idx(1, 1), grid.idx(grid.height - 2, grid.width - 2));
+        grid.set_wall(start, false);
+        grid.set_wall(end, false);
+        grid.set_start(start);
+        grid.set_end(end);
+        grid
+    }
+
+    #[test]
+    fn open_grid_costs_match_dijkstra() {
+        let grid = with_corners(Grid::new(50, 37));
+        assert_eq!(costs(&grid), (Some(81), Some(81)));
+    }
+
+    #[test]
+    fn perfect_maze_costs_match_dijkstra() {
+        // A perfect maze has one route, so the abstraction cannot shortcut it.
+        for seed in 1..4 {
+            macroquad::rand::srand(seed);
+            let mut grid = Grid::new(61, 45);
+            Generator::RecursiveBacktracker.generate(&mut grid, &mut Trace::off(), &mut Settings::default());
+            let grid = with_corners(grid);
+            let (hpa, optimal) = costs(&grid);
+            assert!(optimal.is_some());
+            assert_eq!(hpa, optimal, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn terrain_costs_stay_close_to_dijkstra() {
+        for seed in 1..6 {
+            let mut grid = Grid::new(64, 48);
+            noise::generate(&mut grid, &mut Trace::off(), &noise::Params { seed, ..noise::Params::default() });
+            assert!((0..64 * 48).any(|i| [COST_MUD, COST_WATER].contains(&grid.cell(i).cost)));
+            let grid = with_corners(grid);
+            let (Some(hpa), Some(optimal)) = costs(&grid) else { panic!("seed {seed}: no path") };
+            // HPA* is near-optimal: entrances sit at fixed border cells.
+            assert!(hpa >= optimal && hpa * 4 <= optimal * 5, "seed {seed}: {hpa} vs {optimal}");
+        }
+    }
+
+    #[test]
+    fn walled_off_end_has_no_path() {
+        let mut grid = with_corners(Grid::new(40, 40));
+        for col in 0..40 { grid.set_wall(grid.idx(20, col), true); }
+        assert_eq!(costs(&grid), (None, None));
+    }
+
+    #[test]
+    fn one_wall_rebuilds_only_its_cluster_and_neighbours() {
+        let mut grid = Grid::new(4 * CLUSTER_SIZE, 4 * CLUSTER_SIZE);
+        let mut hierarchy = Hierarchy::new(CLUSTER_SIZE);
+        assert_eq!(hierarchy.update(&mut grid), 16);
+        assert_eq!(hierarchy.update(&mut grid), 0);
+
+        // Inside cluster 5 (row 1, col 1), which has four neighbours.
+        let idx = grid.idx(CLUSTER_SIZE + 3, CLUSTER_SIZE + 3);
+        grid.set_wall(idx, true);
+        assert_eq!(hierarchy.update(&mut grid), 5);
+        assert_eq!(hierarchy.update(&mut grid), 0);
+
+        // A corner cluster has two.
+        grid.set_terrain(0, COST_MUD);
+        assert_eq!(hierarchy.update(&mut grid), 3);
+
+        // A write that changes nothing costs a compare but no rebuild.
+        grid.set_terrain(0, COST_MUD);
+        assert_eq!(hierarchy.update(&mut grid), 0);
+    }
+
+    #[test]
+    fn patched_hierarchy_matches_a_fresh_one() {
+        let mut grid = with_corners(Grid::new(48, 48));
+        let mut hierarchy = Hierarchy::new(CLUSTER_SIZE);
+        hierarchy.update(&mut grid);
+        for row in 5..40 { grid.set_wall(grid.idx(row, 20), true); }
+        grid.set_terrain(grid.idx(30, 30), COST_WATER);
+
+        let mut patched = grid.clone();
+        let mut data = SearchData::new(48 * 48);
+        let stats = run(&mut patched, &mut data, &mut hierarchy);
+        let mut fresh = grid.clone();
+        let mut fresh_data = SearchData::new(48 * 48);
+        let expected = run(&mut fresh, &mut fresh_data, &mut Hierarchy::new(CLUSTER_SIZE));
+        assert_eq!(stats.path_length, expected.path_length);
+        assert_eq!(data.route, fresh_data.route);
+    }
+}
@@ -3,10 +3,111 @@ pub mod bfs;
 pub mod dfs;
 pub mod dijkstra;
 pub mod astar;
+pub mod hpa;
+pub mod wall_follower;
+pub mod tremaux;
+pub mod dead_end_filling;
 
 use crate::grid::Grid;
+use crate::stats::Stats;
+use crate::maze::trace::{Event, Trace};
+use hpa::Hierarchy;
+use wall_follower::Hand;
 
-pub fn reconstruct_path(grid: &mut Grid, parent: &[usize], start: usize, end: usize) -> u32 {
+/// Everything the sidebar can run, in button order.
+pub const NAMES: [&str; 9] = ["BFS", "DFS", "Dijkstra", "A*", "HPA*", "Tremaux", "Wall L", "Wall R", "Dead-end"];
+
+/// Seconds on a monotonic clock, for timing runs. macroquad's clock only
+/// exists once a window is open, which the headless server never does, so
+/// native builds read std's instead (not available on wasm32).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> f64 {
+    static EPOCH: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    EPOCH.get_or_init(std::time::Instant::now).elapsed().as_secs_f64()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> f64 {
+    macroquad::time::get_time()
+}
+
+/// Solvers that walk the maze are only worth watching, so they always record.
+pub fn is_walker(name: &str) -> bool {
+    matches!(name, "Tremaux" | "Wall L" | "Wall R" | "Dead-end")
+}
+
+/// Per-cell numbers from the last search, for the heatmap overlay and the
+/// cell inspector. `u32::MAX` (or `usize::MAX` for parents) marks cells
+/// the search never gave a value.
+#[derive(Clone, Default)]
+pub struct SearchData {
+    /// Cost (or steps, for unweighted searches) from the start.
+    pub g: Vec<u32>,
+    /// Heuristic estimate to the end; only A* has one.
+    pub h: Vec<u32>,
+    /// When the cell was expanded, counting from 0 at the start.
+    pub order: Vec<u32>,
+    /// Expansion step during which the cell was first reached.
+    pub discovered: Vec<u32>,
+    pub parent: Vec<usize>,
+    /// The path found, start to end; empty when there was none.
+    pub route: Vec<usize>,
+}
+
+impl SearchData {
+    pub fn new(size: usize) -> Self {
+        Self {
+            g: vec![u32::MAX; size],
+            h: vec![u32::MAX; size],
+            order: vec![u32::MAX; size],
+            discovered: vec![u32::MAX; size],
+            parent: vec![usize::MAX; size],
+            route: Vec::new(),
+        }
+    }
+
+    pub fn f(&self, idx: usize) -> u32 {
+        if self.g[idx] == u32::MAX || self.h[idx] == u32::MAX { u32::MAX } else { self.g[idx] + self.h[idx] }
+    }
+}
+
+/// Runs the algorithm with the given sidebar name, leaving its per-cell
+/// numbers in `data`. HPA* builds its abstraction into `hpa` on first use
+/// and reuses it after that; `astar` only matters to A*.
+pub fn run(
+    name: &str,
+    grid: &mut Grid,
+    trace: &mut Trace,
+    data: &mut SearchData,
+    hpa: &mut Option<Hierarchy>,
+    astar: &astar::Params,
+) -> Stats {
+    *data = SearchData::new(grid.width * grid.height);
+    let mut stats = match name {
+        "BFS"      => bfs::run(grid, trace, data),
+        "DFS"      => dfs::run(grid, trace, data),
+        "Dijkstra" => dijkstra::run(grid, trace, data),
+        "A*"       => astar::run(grid, trace, data, astar),
+        "HPA*"     => hpa::run(grid, data, hpa.get_or_insert_with(|| Hierarchy::new(hpa::CLUSTER_SIZE))),
+        "Tremaux"  => tremaux::run(grid, trace, data),
+        "Wall L"   => wall_follower::run(grid, trace, data, Hand::Left),
+        "Wall R"   => wall_follower::run(grid, trace, data, Hand::Right),
+        "Dead-end" => dead_end_filling::run(grid, trace, data),
+        _          => Stats::default(),
+    };
+    stats.measure_path(grid);
+    stats
+}
+
+/// Puts a reached cell in the open set, once.
+pub fn open_cell(grid: &mut Grid, trace: &mut Trace, idx: usize) {
+    let cell = grid.cell_mut(idx);
+    if cell.is_open || cell.is_start { return; }
+    cell.is_open = true;
+    trace.push(Event::Open(idx));
+}
+
+pub fn reconstruct_path(grid: &mut Grid, trace: &mut Trace, parent: &[usize], start: usize, end: usize) -> u32 {
     let mut length = 0;
     let mut current = end;
 
@@ -16,11 +117,263 @@ pub fn reconstruct_path(grid: &mut Grid, parent: &[usize], start: usize, end: us
 
     while current != start {
         let cell = grid.cell_mut(current);
-        if !cell.is_end { cell.is_path = true; }
+        if !cell.is_end {
+            cell.is_path = true;
+            trace.push(Event::Path(current));
+        }
         current = parent[current];
         length += 1;
         if current == usize::MAX { return 0; }
     }
 
     length
-}
\ No newline at end of file
+}
+
+/// The cells from start to end by way of `parent`, or none if the end was
+/// never reached.
+pub fn route_to(parent: &[usize], start: usize, end: usize) -> Vec<usize> {
+    let mut route = vec![end];
+    let mut current = end;
+    while current != start {
+        current = parent[current];
+        if current == usize::MAX { return Vec::new(); }
+        route.push(current);
+    }
+    route.reverse();
+    route
+}
+
+/// Marks a walked route from start to end as the path, leaving the
+/// endpoints themselves alone. Returns its length in steps.
+pub fn mark_route(grid: &mut Grid, trace: &mut Trace, route: &[usize]) -> u32 {
+    for &idx in route {
+        let cell = grid.cell_mut(idx);
+        if !cell.is_start && !cell.is_end {
+            cell.is_path = true;
+            trace.push(Event::Path(idx));
+        }
+    }
+    route.len().saturating_sub(1) as u32
+}
+
+/// Headings in clockwise order: north, east, south, west.
+pub const HEADINGS: [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// The open cell one step from `idx` in `heading`, if there is one.
+pub fn ahead(grid: &Grid, idx: usize, heading: usize) -> Option<usize> {
+    let (dr, dc) = HEADINGS[heading];
+    let row = (idx / grid.width) as i32 + dr;
+    let col = (idx % grid.width) as i32 + dc;
+    if row < 0 || col < 0 || row >= grid.height as i32 || col >= grid.width as i32 { return None; }
+    let next = grid.idx(row as usize, col as usize);
+    (!grid.cell(next).is_wall).then_some(next)
+}
+
+/// The trail of a solver that walks the maze cell by cell. Stepping back
+/// onto a cell already on the trail cuts off the loop in between, so
+/// `route` is always a simple path from the start to where the walker is.
+pub struct Walk {
+    pub route: Vec<usize>,
+    position: Vec<usize>,
+    /// First-visit order and trail length at that moment, per cell.
+    order: Vec<u32>,
+    depth: Vec<u32>,
+    parent: Vec<usize>,
+    longest: usize,
+    pub steps: u32,
+    pub explored: u32,
+}
+
+impl Walk {
+    pub fn new(grid: &Grid, start: usize) -> Self {
+        let size = grid.width * grid.height;
+        let mut walk = Self {
+            route: vec![start],
+            position: vec![usize::MAX; size],
+            order: vec![u32::MAX; size],
+            depth: vec![u32::MAX; size],
+            parent: vec![usize::MAX; size],
+            longest: 1,
+            steps: 0,
+            explored: 1,
+        };
+        walk.position[start] = 0;
+        walk.order[start] = 0;
+        walk.depth[start] = 0;
+        walk
+    }
+
+    pub fn current(&self) -> usize {
+        *self.route.last().unwrap()
+    }
+
+    pub fn step(&mut self, grid: &mut Grid, trace: &mut Trace, next: usize) {
+        self.steps += 1;
+        trace.push(Event::Head(next));
+        if self.order[next] == u32::MAX {
+            self.parent[next] = self.current();
+            self.order[next] = self.explored;
+            self.depth[next] = self.route.len() as u32;
+            self.explored += 1;
+            let cell = grid.cell_mut(next);
+            if !cell.is_end {
+                cell.is_visited = true;
+                trace.push(Event::Visit(next));
+            }
+        }
+
+        match self.position[next] {
+            usize::MAX => {
+                self.position[next] = self.route.len();
+                self.route.push(next);
+                self.longest = self.longest.max(self.route.len());
+                trace.push(Event::Mark(next));
+            }
+            at => {
+                for &idx in &self.route[at + 1..] {
+                    self.position[idx] = usize::MAX;
+                    trace.push(Event::Unmark(idx));
+                }
+                self.route.truncate(at + 1);
+            }
+        }
+    }
+
+    /// Marks the route if the walker got to the end and sums up the run.
+    /// Visit order, trail depth and the cell first arrived from go to `data`.
+    /// A walker reaches and leaves a cell in one go, so found = expanded.
+    /// `extra_bytes` is whatever the solver kept besides the trail itself.
+    pub fn finish(
+        self,
+        grid: &mut Grid,
+        trace: &mut Trace,
+        data: &mut SearchData,
+        reached: bool,
+        start_time: f64,
+        extra_bytes: usize,
+    ) -> Stats {
+        let path_length = if reached { mark_route(grid, trace, &self.route) } else { 0 };
+        let trail_bytes = self.position.len() * (2 * size_of::<usize>() + 2 * size_of::<u32>())
+            + self.longest * size_of::<usize>();
+        data.discovered.clone_from(&self.order);
+        data.order = self.order;
+        data.g = self.depth;
+        data.parent = self.parent;
+        data.route = if reached { self.route } else { Vec::new() };
+        let execution_ms = (now() - start_time) * 1000.0;
+        Stats {
+            nodes_explored: self.explored,
+            path_length,
+            execution_ms,
+            path_found: reached,
+            steps: self.steps,
+            peak_bytes: trail_bytes + extra_bytes,
+            ..Stats::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::{braid, Generator, Settings};
+
+    fn grid(rows: &[&str]) -> Grid {
+        Grid::from_text(&rows.join("\n")).unwrap()
+    }
+
+    /// A perfect maze with the endpoints in opposite corners.
+    fn maze(seed: u64, width: usize, height: usize) -> Grid {
+        macroquad::rand::srand(seed);
+        let mut grid = Grid::new(width, height);
+        Generator::RecursiveBacktracker.generate(&mut grid, &mut Trace::off(), &mut Settings::default());
+        grid.set_start(grid.idx(1, 1));
+        grid.set_end(grid.idx(height - 2, width - 2));
+        grid
+    }
+
+    fn solve(name: &str, grid: &Grid) -> (Grid, SearchData, Stats) {
+        let mut grid = grid.clone();
+        let mut data = SearchData::default();
+        let stats = run(name, &mut grid, &mut Trace::off(), &mut data, &mut None, &astar::Params::default());
+        (grid, data, stats)
+    }
+
+    fn assert_walks(grid: &Grid, route: &[usize]) {
+        assert_eq!(route.first().copied(), grid.start());
+        assert_eq!(route.last().copied(), grid.end());
+        for pair in route.windows(2) {
+            assert!(grid.neighbors(pair[0]).contains(&pair[1]), "{} -> {} is not a step", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn wall_follower_reports_circling_an_island() {
+        // The end is beside a free-standing pillar, so a hand kept on the
+        // outer wall never gets to it.
+        let room = grid(&[
+            "#######",
+            "#S....#",
+            "#.....#",
+            "#..#E.#",
+            "#.....#",
+            "#.....#",
+            "#######",
+        ]);
+        for name in ["Wall L", "Wall R"] {
+            let (_, data, stats) = solve(name, &room);
+            assert!(stats.looped, "{name}");
+            assert!(!stats.path_found, "{name}");
+            assert!(data.route.is_empty(), "{name}");
+        }
+    }
+
+    #[test]
+    fn wall_follower_solves_perfect_mazes() {
+        for seed in 1..6 {
+            let grid = maze(seed, 31, 21);
+            for name in ["Wall L", "Wall R"] {
+                let (_, data, stats) = solve(name, &grid);
+                assert!(stats.path_found && !stats.looped, "{name} seed {seed}");
+                assert_walks(&grid, &data.route);
+            }
+        }
+    }
+
+    #[test]
+    fn tremaux_reaches_the_end_of_braided_mazes() {
+        for seed in 1..6 {
+            let mut grid = maze(seed, 31, 21);
+            braid::braid(&mut grid, &mut Trace::off(), 1.0);
+            assert!((0..31 * 21).all(|i| grid.cell(i).is_wall || grid.neighbors(i).len() != 1));
+            let (_, data, stats) = solve("Tremaux", &grid);
+            assert!(stats.path_found, "seed {seed}");
+            assert_walks(&grid, &data.route);
+            // The trail drops every loop it closes, so no cell repeats.
+            let mut cells = data.route.clone();
+            cells.sort_unstable();
+            cells.dedup();
+            assert_eq!(cells.len(), data.route.len(), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn dead_end_filling_leaves_only_the_solution() {
+        for seed in 1..6 {
+            let grid = maze(seed, 31, 21);
+            let (filled, data, stats) = solve("Dead-end", &grid);
+            let (_, _, shortest) = solve("BFS", &grid);
+            assert!(stats.path_found, "seed {seed}");
+            assert_eq!(stats.path_length, shortest.path_length, "seed {seed}");
+            assert_walks(&grid, &data.route);
+
+            let mut left: Vec<usize> = (0..31 * 21)
+                .filter(|&i| !filled.cell(i).is_wall && !filled.cell(i).is_visited)
+                .collect();
+            let mut route = data.route.clone();
+            left.sort_unstable();
+            route.sort_unstable();
+            assert_eq!(left, route, "seed {seed}");
+        }
+    }
+}
@@ -0,0 +1,49 @@
+// src/algorithms/tremaux.rs
+use crate::grid::Grid;
+use crate::stats::Stats;
+use crate::maze::trace::Trace;
+use super::{ahead, now, SearchData, Walk};
+
+/// Tremaux's algorithm: chalk a mark on every passage each time it is
+/// walked, never take a passage with two marks, and turn back on reaching
+/// a visited cell through a fresh passage. Every passage is walked at most
+/// twice, and once the exit is found the once-marked passages lead to it.
+pub fn run(grid: &mut Grid, trace: &mut Trace, data: &mut SearchData) -> Stats {
+    let start_time = now();
+    let (Some(start), Some(end)) = (grid.start(), grid.end()) else {
+        return Stats::default();
+    };
+    trace.begin(grid);
+
+    let size = grid.width * grid.height;
+    // Marks per cell and heading; both ends of a passage are kept in step.
+    let mut marks = vec![[0u8; 4]; size];
+    let mut arrived = vec![false; size];
+    let mut walk = Walk::new(grid, start);
+    let mut back = None;
+    let mut revisit = false;
+    arrived[start] = true;
+
+    while walk.current() != end {
+        let current = walk.current();
+        let choice = match back {
+            Some(b) if revisit && marks[current][b] == 1 => Some(b),
+            _ => (0..4)
+                .filter(|&d| marks[current][d] < 2 && ahead(grid, current, d).is_some())
+                .min_by_key(|&d| (marks[current][d], Some(d) == back)),
+        };
+        let Some(heading) = choice else { break };
+        let Some(next) = ahead(grid, current, heading) else { break };
+
+        marks[current][heading] += 1;
+        marks[next][(heading + 2) % 4] += 1;
+        back = Some((heading + 2) % 4);
+        revisit = arrived[next];
+        arrived[next] = true;
+        walk.step(grid, trace, next);
+    }
+
+    let reached = walk.current() == end;
+    let mark_bytes = size * (size_of::<[u8; 4]>() + size_of::<bool>());
+    walk.finish(grid, trace, data, reached, start_time, mark_bytes)
+}
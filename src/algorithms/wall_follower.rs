@@ -0,0 +1,64 @@
+// src/algorithms/wall_follower.rs
+use crate::grid::Grid;
+use crate::stats::Stats;
+use crate::maze::trace::Trace;
+use super::{ahead, now, SearchData, Walk};
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// Keeps one hand on the wall. The walker's whole state is its cell and
+/// heading, so meeting the same pair twice means it is circling a wall
+/// section that isn't attached to the one around the exit.
+pub fn run(grid: &mut Grid, trace: &mut Trace, data: &mut SearchData, hand: Hand) -> Stats {
+    let start_time = now();
+    let (Some(start), Some(end)) = (grid.start(), grid.end()) else {
+        return Stats::default();
+    };
+    trace.begin(grid);
+
+    // Quarter turns clockwise to try, in order: hand side, straight, other side, back.
+    let (turns, away) = match hand {
+        Hand::Left  => ([3, 0, 1, 2], 1),
+        Hand::Right => ([1, 0, 3, 2], 3),
+    };
+    let mut walk = Walk::new(grid, start);
+    let mut heading = 1;
+
+    // Out in the open, walk straight until the hand finds a wall.
+    while walk.current() != end && ahead(grid, walk.current(), (heading + turns[0]) % 4).is_some() {
+        match ahead(grid, walk.current(), heading) {
+            Some(next) => walk.step(grid, trace, next),
+            None => {
+                heading = (heading + away) % 4;
+                break;
+            }
+        }
+    }
+
+    let mut seen = vec![false; grid.width * grid.height * 4];
+    let mut looped = false;
+    while walk.current() != end {
+        let current = walk.current();
+        if seen[current * 4 + heading] {
+            looped = true;
+            break;
+        }
+        seen[current * 4 + heading] = true;
+
+        let Some((turn, next)) = turns.iter()
+            .find_map(|&turn| ahead(grid, current, (heading + turn) % 4).map(|next| (turn, next)))
+        else {
+            break;
+        };
+        heading = (heading + turn) % 4;
+        walk.step(grid, trace, next);
+    }
+
+    let reached = walk.current() == end;
+    let seen_bytes = seen.len() * size_of::<bool>();
+    Stats { looped, ..walk.finish(grid, trace, data, reached, start_time, seen_bytes) }
+}
@@ -0,0 +1,186 @@
+// src/analysis.rs
+use std::collections::VecDeque;
+use crate::grid::Grid;
+
+/// Shape metrics of whatever is on the grid, counted over open cells and
+/// their 4-neighbours, so they apply to caves and dungeons as well as mazes.
+#[derive(Default, Clone, Copy)]
+pub struct Report {
+    pub open_cells: u32,
+    /// Open cells with exactly one open neighbour.
+    pub dead_ends: u32,
+    /// Open cells by number of open neighbours; 3 and 4 are junctions.
+    pub degrees: [u32; 5],
+    /// Longest shortest path in steps, within the start's region (or the
+    /// largest one). Exact on perfect mazes, a lower bound once there are loops.
+    pub diameter: u32,
+    /// Steps between start and end, if they are connected.
+    pub solution: Option<u32>,
+    /// Mean steps between two cells that are not plain corridor.
+    pub avg_corridor: f32,
+    /// Share of open cells that are plain corridor (two open neighbours).
+    /// Long flowing passages push it toward 1, bushy branching toward 0.
+    pub river: f32,
+}
+
+pub fn analyze(grid: &Grid) -> Report {
+    let size = grid.width * grid.height;
+    let mut report = Report::default();
+    let degree: Vec<usize> = (0..size)
+        .map(|i| if grid.cell(i).is_wall { 0 } else { grid.neighbors(i).len() })
+        .collect();
+
+    for i in (0..size).filter(|&i| !grid.cell(i).is_wall) {
+        report.open_cells += 1;
+        report.degrees[degree[i]] += 1;
+    }
+    if report.open_cells == 0 { return report; }
+    report.dead_ends = report.degrees[1];
+    report.river = report.degrees[2] as f32 / report.open_cells as f32;
+
+    // Corridors run between cells that are not plain corridor. Each one is
+    // walked from both ends, which doubles both sums and leaves the mean alone.
+    let (mut steps, mut corridors) = (0u64, 0u64);
+    for node in (0..size).filter(|&i| degree[i] > 0 && degree[i] != 2) {
+        for first in grid.neighbors(node) {
+            let (mut prev, mut current, mut length) = (node, first, 1);
+            while degree[current] == 2 {
+                let next = grid.neighbors(current).into_iter().find(|&n| n != prev).unwrap_or(prev);
+                (prev, current) = (current, next);
+                length += 1;
+            }
+            steps += length;
+            corridors += 1;
+        }
+    }
+    if corridors > 0 { report.avg_corridor = steps as f32 / corridors as f32; }
+
+    if let (Some(start), Some(end)) = (grid.start(), grid.end()) {
+        let dist = distances(grid, start);
+        if dist[end] != u32::MAX { report.solution = Some(dist[end]); }
+    }
+
+    // Double sweep: the farthest cell from anywhere is one end of a diameter.
+    let origin = grid.start()
+        .filter(|&s| !grid.cell(s).is_wall)
+        .unwrap_or_else(|| largest_region_cell(grid));
+    let (far, _) = farthest(&distances(grid, origin));
+    (_, report.diameter) = farthest(&distances(grid, far));
+
+    report
+}
+
+/// Unweighted BFS steps from `from`; unreachable cells hold `u32::MAX`.
+pub fn distances(grid: &Grid, from: usize) -> Vec<u32> {
+    let mut dist = vec![u32::MAX; grid.width * grid.height];
+    let mut queue = VecDeque::new();
+    dist[from] = 0;
+    queue.push_back(from);
+    while let Some(current) = queue.pop_front() {
+        for neighbor in grid.neighbors(current) {
+            if dist[neighbor] == u32::MAX {
+                dist[neighbor] = dist[current] + 1;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    dist
+}
+
+fn farthest(dist: &[u32]) -> (usize, u32) {
+    dist.iter()
+        .copied()
+        .enumerate()
+        .filter(|&(_, d)| d != u32::MAX)
+        .max_by_key(|&(_, d)| d)
+        .unwrap_or((0, 0))
+}
+
+fn largest_region_cell(grid: &Grid) -> usize {
+    let size = grid.width * grid.height;
+    let mut seen = vec![false; size];
+    let (mut best, mut best_size) = (0, 0);
+    for i in 0..size {
+        if grid.cell(i).is_wall || seen[i] { continue; }
+        let mut stack = vec![i];
+        let mut count = 0;
+        seen[i] = true;
+        while let Some(current) = stack.pop() {
+            count += 1;
+            for neighbor in grid.neighbors(current) {
+                if !seen[neighbor] {
+                    seen[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        if count > best_size { (best, best_size) = (i, count); }
+    }
+    best
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &[&str]) -> Grid {
+        Grid::from_text(&rows.join("\n")).unwrap()
+    }
+
+    #[test]
+    fn measures_a_hand_drawn_maze() {
+        // One junction at (3, 5) with arms of 12, 2 and 2 steps.
+        let report = analyze(&grid(&[
+            "#######",
+            "#S..#.#",
+            "#.#.#.#",
+            "#.#...#",
+            "#.###.#",
+            "#...#E#",
+            "#######",
+        ]));
+        assert_eq!(report.open_cells, 17);
+        assert_eq!(report.dead_ends, 3);
+        assert_eq!(report.degrees, [0, 3, 13, 1, 0]);
+        assert_eq!(report.diameter, 14);
+        assert_eq!(report.solution, Some(8));
+        assert!((report.avg_corridor - 16.0 / 3.0).abs() < 1e-5, "{}", report.avg_corridor);
+        assert!((report.river - 13.0 / 17.0).abs() < 1e-5, "{}", report.river);
+    }
+
+    #[test]
+    fn a_loop_has_no_dead_ends_or_corridor_ends() {
+        let report = analyze(&grid(&["#####", "#S..#", "#.#.#", "#..E#", "#####"]));
+        assert_eq!(report.degrees, [0, 0, 8, 0, 0]);
+        assert_eq!(report.dead_ends, 0);
+        assert_eq!(report.diameter, 4);
+        assert_eq!(report.solution, Some(4));
+        assert_eq!(report.avg_corridor, 0.0);
+        assert_eq!(report.river, 1.0);
+    }
+
+    #[test]
+    fn walled_off_end_has_no_solution() {
+        let report = analyze(&grid(&["#######", "#S.#..#", "#..#.E#", "#######"]));
+        assert_eq!(report.solution, None);
+        // Measured in the start's region.
+        assert_eq!(report.diameter, 2);
+    }
+
+    #[test]
+    fn largest_region_is_used_without_a_start() {
+        let report = analyze(&grid(&["#######", "#.#...#", "#.#...#", "#######"]));
+        assert_eq!(report.open_cells, 8);
+        assert_eq!(report.solution, None);
+        assert_eq!(report.diameter, 3);
+    }
+
+    #[test]
+    fn all_walls_is_empty() {
+        let report = analyze(&grid(&["###", "###", "###"]));
+        assert_eq!(report.open_cells, 0);
+        assert_eq!(report.diameter, 0);
+        assert_eq!(report.solution, None);
+    }
+}
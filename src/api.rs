@@ -0,0 +1,218 @@
+// src/api.rs
+use crate::algorithms::astar::{Heuristic, Params};
+use crate::grid::Grid;
+use crate::maze::Generator;
+use crate::maze::mask::Scope;
+use crate::{algorithms, fits, link, AppState, LARGE_GRID_SIZE};
+
+/// A request from outside the window: the hosting page in the web build,
+/// or a script talking to the headless server.
+pub enum Command {
+    /// An empty grid of the given width and height.
+    Create(usize, usize),
+    /// A grid link (URL, fragment or bare code) or a grid in text form.
+    Load(String),
+    /// Builds (`true`) or clears walls on the given (row, col) cells.
+    Walls(Vec<(usize, usize)>, bool),
+    /// Sets the cost of the given (row, col) cells; walls are left alone.
+    Terrain(Vec<(usize, usize)>, u16),
+    /// Runs a generator over the whole grid, reseeding the generators first
+    /// when a seed is given so the same seed gives the same maze.
+    Generate(Generator, Option<u64>),
+    SetStart(usize, usize),
+    SetEnd(usize, usize),
+    /// Runs the named algorithm, or the last one again.
+    Run(Option<String>),
+}
+
+/// Applies `command`, or says why it could not be.
+pub fn apply(state: &mut AppState, command: Command) -> Result<(), String> {
+    match command {
+        Command::Create(width, height) => {
+            if !fits(width, height) {
+                return Err(format!("grid sides must be 3 to {LARGE_GRID_SIZE}"));
+            }
+            state.resize(width, height);
+        }
+        Command::Load(text) => {
+            let (grid, algo) = parse_map(&text).ok_or("not a grid link or grid text")?;
+            state.load_grid(grid, algo)?;
+        }
+        Command::Walls(cells, wall) => {
+            for idx in edit_cells(state, &cells)? { state.grid.set_wall(idx, wall); }
+        }
+        Command::Terrain(cells, cost) => {
+            for idx in edit_cells(state, &cells)? { state.grid.set_terrain(idx, cost); }
+        }
+        Command::Generate(generator, seed) => {
+            if let Some(seed) = seed {
+                macroquad::rand::srand(seed);
+                state.maze_settings.noise.seed = seed as u32;
+                state.maze_settings.wfc.seed = seed as u32;
+            }
+            state.maze_generator = generator;
+            state.maze_scope = Scope::All;
+            state.generate_maze(false);
+            if generator == Generator::WaveCollapse && !state.maze_settings.wfc.generated {
+                return Err(format!("{}: {}", generator.name(), state.maze_settings.wfc.report));
+            }
+        }
+        Command::SetStart(row, col) => {
+            let idx = open_cell(state, row, col)?;
+            state.grid.set_start(idx);
+        }
+        Command::SetEnd(row, col) => {
+            let idx = open_cell(state, row, col)?;
+            state.grid.set_end(idx);
+        }
+        Command::Run(name) => {
+            let algo = match name {
+                Some(name) => algorithm(&name)?,
+                None       => state.last_algo.ok_or("no algorithm has been run yet")?,
+            };
+            state.run_algo(algo);
+        }
+    }
+    Ok(())
+}
+
+/// Link codes first, then the `#`/`.` text form.
+pub fn parse_map(text: &str) -> Option<(Grid, Option<&'static str>)> {
+    link::decode(text).or_else(|| Grid::from_text(text).map(|grid| (grid, None)))
+}
+
+/// A sidebar name as something easy to type: lower case, letters and
+/// digits only, `*` spelled out. "A*" is `astar`, "Prim's" is `prims`.
+pub fn key(name: &str) -> String {
+    name.replace('*', "star").chars().filter(char::is_ascii_alphanumeric).map(|c| c.to_ascii_lowercase()).collect()
+}
+
+/// Looks up an algorithm by its sidebar name or its `key`.
+pub fn algorithm(name: &str) -> Result<&'static str, String> {
+    let wanted = key(name);
+    algorithms::NAMES.into_iter().find(|algo| key(algo) == wanted).ok_or_else(|| {
+        format!("unknown algorithm '{name}', expected one of {}", algorithms::NAMES.join(", "))
+    })
+}
+
+/// Looks up a generator by its sidebar name or its `key`.
+pub fn generator(name: &str) -> Result<Generator, String> {
+    let wanted = key(name);
+    Generator::ALL.into_iter().find(|g| key(g.name()) == wanted).ok_or_else(|| {
+        let names: Vec<&str> = Generator::ALL.iter().map(|g| g.name()).collect();
+        format!("unknown generator '{name}', expected one of {}", names.join(", "))
+    })
+}
+
+/// Looks up an A* heuristic by name or `key`.
+pub fn heuristic(name: &str) -> Result<Heuristic, String> {
+    let wanted = key(name);
+    Heuristic::ALL.into_iter().find(|h| key(h.name()) == wanted).ok_or_else(|| {
+        let names: Vec<&str> = Heuristic::ALL.iter().map(|h| h.name()).collect();
+        format!("unknown heuristic '{name}', expected one of {}", names.join(", "))
+    })
+}
+
+/// Checks a weighted-A* weight.
+pub fn weight(weight: f32) -> Result<f32, String> {
+    if (0.0..=Params::MAX_WEIGHT).contains(&weight) { Ok(weight) } else {
+        Err(format!("weight must be between 0 and {}", Params::MAX_WEIGHT))
+    }
+}
+
+/// Checks every cell before anything changes, then clears any search so the
+/// edit does not leave a stale result on screen.
+fn edit_cells(state: &mut AppState, cells: &[(usize, usize)]) -> Result<Vec<usize>, String> {
+    let (width, height) = (state.grid.width, state.grid.height);
+    let indices = cells.iter()
+        .map(|&(row, col)| if row < height && col < width { Ok(state.grid.idx(row, col)) } else {
+            Err(format!("row {row}, col {col} is outside the {width}x{height} grid"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    state.skip_playback();
+    state.panes.clear();
+    state.reset_search();
+    Ok(indices)
+}
+
+/// Makes the cell walkable so an endpoint can go there.
+fn open_cell(state: &mut AppState, row: usize, col: usize) -> Result<usize, String> {
+    let idx = edit_cells(state, &[(row, col)])?[0];
+    if state.grid.cell(idx).is_wall { state.grid.set_wall(idx, false); }
+    Ok(idx)
+}
+
+/// The `algoLab` object the hosting page defines (see `index.html`). The
+/// page pushes commands onto `algoLab._queue`; each frame they are applied
+/// here, and `algoLab._emit(type, detail)` reports back `ready`, `error`
+/// and `runfinished` events.
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub mod page {
+    use js_sys::wasm_bindgen::{JsCast, JsValue};
+    use js_sys::{Array, Object, JSON};
+    use super::Command;
+    use crate::{algorithms, web, AppState};
+
+    pub struct Page {
+        /// `AppState::runs` when the last result went out.
+        reported: u32,
+        ready: bool,
+    }
+
+    impl Page {
+        pub fn new(state: &AppState) -> Self {
+            Self { reported: state.runs, ready: false }
+        }
+
+        pub fn tick(&mut self, state: &mut AppState) {
+            // The page may define the object after the module has started.
+            let Some(lab) = web::global("algoLab") else { return };
+            if !self.ready {
+                let names: Array = algorithms::NAMES.iter().map(|&name| JsValue::from_str(name)).collect();
+                web::set(&lab, "algorithms", &names);
+                emit(&lab, "ready", &names);
+                self.ready = true;
+            }
+
+            if let Some(queue) = web::get(&lab, "_queue").and_then(|queue| queue.dyn_into::<Object>().ok()) {
+                while let Some(item) = web::call(&queue, "shift", &[]).filter(|item| !item.is_undefined()) {
+                    if let Err(message) = command(&item).and_then(|command| super::apply(state, command)) {
+                        emit(&lab, "error", &JsValue::from_str(&message));
+                    }
+                }
+            }
+
+            // Animated runs finish when their playback does.
+            if state.runs != self.reported && state.maze_playback.is_none() {
+                self.reported = state.runs;
+                let stats = JSON::parse(&state.stats.to_json(state.last_algo)).unwrap_or(JsValue::NULL);
+                web::set(&lab, "stats", &stats);
+                emit(&lab, "runfinished", &stats);
+            }
+        }
+    }
+
+    fn emit(lab: &Object, kind: &str, detail: &JsValue) {
+        web::call(lab, "_emit", &[JsValue::from_str(kind), detail.clone()]);
+    }
+
+    /// Reads `{ cmd, ... }` as pushed by the page's helper methods.
+    fn command(item: &JsValue) -> Result<Command, String> {
+        let item: &Object = item.dyn_ref().ok_or("commands must be objects")?;
+        let text = |name| web::get(item, name).and_then(|value| value.as_string());
+        let cell = || {
+            let number = |name| web::get(item, name)
+                .and_then(|value| value.as_f64())
+                .filter(|n| *n >= 0.0 && n.fract() == 0.0)
+                .map(|n| n as usize);
+            number("row").zip(number("col")).ok_or("row and col must be whole numbers from 0")
+        };
+        match text("cmd").as_deref() {
+            Some("load")  => Ok(Command::Load(text("map").ok_or("load needs a map string")?)),
+            Some("start") => cell().map(|(row, col)| Command::SetStart(row, col)).map_err(str::to_string),
+            Some("end")   => cell().map(|(row, col)| Command::SetEnd(row, col)).map_err(str::to_string),
+            Some("run")   => Ok(Command::Run(text("algo"))),
+            other         => Err(format!("unknown command {}", other.unwrap_or("(none)"))),
+        }
+    }
+}
@@ -0,0 +1,251 @@
+// src/brush.rs
+use crate::grid::Grid;
+
+/// Largest brush radius the sidebar offers.
+pub const MAX_RADIUS: usize = 8;
+
+/// How a painting tool lays its cells down.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// Freehand strokes, joined up between frames.
+    Free,
+    /// Straight line from press to release.
+    Line,
+    /// Rectangle with the press and release cells as corners.
+    Rect,
+    /// The connected region of the clicked cell's terrain.
+    Fill,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 4] = [Shape::Free, Shape::Line, Shape::Rect, Shape::Fill];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shape::Free => "Free",
+            Shape::Line => "Line",
+            Shape::Rect => "Rect",
+            Shape::Fill => "Fill",
+        }
+    }
+}
+
+/// A drag in progress.
+#[derive(Clone, Copy)]
+pub struct Stroke {
+    pub anchor: usize,
+    /// Cell under the mouse on the latest frame of the drag.
+    pub last: usize,
+    /// Dragging with the right button, which erases.
+    pub erase: bool,
+}
+
+#[derive(Clone, Copy)]
+pub struct Brush {
+    pub shape: Shape,
+    /// 0 paints single cells; otherwise every cell within this distance.
+    pub radius: usize,
+    /// Rectangles are solid rather than outlined.
+    pub filled: bool,
+    pub stroke: Option<Stroke>,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self { shape: Shape::Free, radius: 0, filled: false, stroke: None }
+    }
+}
+
+impl Brush {
+    /// Cells a Line or Rect drag from `from` to `to` covers, brush width included.
+    pub fn span(&self, grid: &Grid, from: usize, to: usize) -> Vec<usize> {
+        let cells = match self.shape {
+            Shape::Rect => rect(grid, from, to, self.filled),
+            _           => line(grid, from, to),
+        };
+        self.stamp(grid, &cells)
+    }
+
+    /// Widens each cell to a disc of `radius`, without repeats.
+    pub fn stamp(&self, grid: &Grid, cells: &[usize]) -> Vec<usize> {
+        if self.radius == 0 { return cells.to_vec(); }
+        let r = self.radius as isize;
+        let mut out = Vec::with_capacity(cells.len() * (2 * self.radius + 1).pow(2));
+        for &idx in cells {
+            let (row, col) = ((idx / grid.width) as isize, (idx % grid.width) as isize);
+            for dr in -r..=r {
+                for dc in -r..=r {
+                    if dr * dr + dc * dc > r * r { continue; }
+                    let (nr, nc) = (row + dr, col + dc);
+                    if nr >= 0 && nc >= 0 && (nr as usize) < grid.height && (nc as usize) < grid.width {
+                        out.push(grid.idx(nr as usize, nc as usize));
+                    }
+                }
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+        out
+    }
+}
+
+/// Bresenham's line from `from` to `to`, both ends included.
+pub fn line(grid: &Grid, from: usize, to: usize) -> Vec<usize> {
+    let (mut r, mut c) = ((from / grid.width) as isize, (from % grid.width) as isize);
+    let (r1, c1) = ((to / grid.width) as isize, (to % grid.width) as isize);
+    let (dr, dc) = ((r1 - r).abs(), -(c1 - c).abs());
+    let (step_r, step_c) = ((r1 - r).signum(), (c1 - c).signum());
+    let mut err = dr + dc;
+    let mut cells = Vec::with_capacity((dr - dc) as usize + 1);
+    loop {
+        cells.push(grid.idx(r as usize, c as usize));
+        if r == r1 && c == c1 { return cells; }
+        let e2 = 2 * err;
+        if e2 >= dc { err += dc; r += step_r; }
+        if e2 <= dr { err += dr; c += step_c; }
+    }
+}
+
+/// The rectangle with corners `a` and `b`, solid or just its border.
+pub fn rect(grid: &Grid, a: usize, b: usize, filled: bool) -> Vec<usize> {
+    let (r0, r1) = ((a / grid.width).min(b / grid.width), (a / grid.width).max(b / grid.width));
+    let (c0, c1) = ((a % grid.width).min(b % grid.width), (a % grid.width).max(b % grid.width));
+    (r0..=r1)
+        .flat_map(|row| (c0..=c1).map(move |col| (row, col)))
+        .filter(|&(row, col)| filled || row == r0 || row == r1 || col == c0 || col == c1)
+        .map(|(row, col)| grid.idx(row, col))
+        .collect()
+}
+
+/// Cells 4-connected to `seed` with the same terrain: walls, or floor of
+/// the same cost.
+pub fn flood(grid: &Grid, seed: usize) -> Vec<usize> {
+    let kind = |idx: usize| (grid.cell(idx).is_wall, grid.cell(idx).cost);
+    let target = kind(seed);
+    let mut seen = vec![false; grid.width * grid.height];
+    let mut stack = vec![seed];
+    let mut cells = Vec::new();
+    seen[seed] = true;
+    while let Some(idx) = stack.pop() {
+        cells.push(idx);
+        // Not `Grid::neighbors`, which skips walls.
+        let (row, col) = (idx / grid.width, idx % grid.width);
+        let around = [
+            (row > 0).then(|| idx - grid.width),
+            (row + 1 < grid.height).then(|| idx + grid.width),
+            (col > 0).then(|| idx - 1),
+            (col + 1 < grid.width).then(|| idx + 1),
+        ];
+        for next in around.into_iter().flatten() {
+            if !seen[next] && kind(next) == target {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{COST_MUD, COST_WATER};
+
+    fn at(grid: &Grid, cells: &[usize]) -> Vec<(usize, usize)> {
+        cells.iter().map(|&idx| (idx / grid.width, idx % grid.width)).collect()
+    }
+
+    #[test]
+    fn line_is_a_thin_unbroken_run_in_every_octant() {
+        let grid = Grid::new(21, 21);
+        let center = grid.idx(10, 10);
+        let targets = [
+            (0, 13), (0, 17), (3, 20), (7, 20), (13, 20), (17, 20), (20, 17), (20, 13),
+            (20, 7), (20, 3), (17, 0), (13, 0), (7, 0), (3, 0), (0, 3), (0, 7),
+            (0, 10), (10, 20), (20, 10), (10, 0), (0, 0), (0, 20), (20, 20), (20, 0), (10, 10),
+        ];
+        for (row, col) in targets {
+            let to = grid.idx(row, col);
+            let cells = line(&grid, center, to);
+            let (dr, dc) = (row as f32 - 10.0, col as f32 - 10.0);
+            assert_eq!(cells.first(), Some(&center), "to {row},{col}");
+            assert_eq!(cells.last(), Some(&to), "to {row},{col}");
+            assert_eq!(cells.len(), dr.abs().max(dc.abs()) as usize + 1, "to {row},{col}");
+            for (a, b) in at(&grid, &cells).windows(2).map(|pair| (pair[0], pair[1])) {
+                assert_eq!(a.0.abs_diff(b.0).max(a.1.abs_diff(b.1)), 1, "to {row},{col}: gap at {a:?}");
+            }
+            // No cell strays more than half a cell from the true line.
+            let length = (dr * dr + dc * dc).sqrt().max(1.0);
+            for (r, c) in at(&grid, &cells) {
+                let off = ((r as f32 - 10.0) * dc - (c as f32 - 10.0) * dr).abs() / length;
+                assert!(off <= 0.5 + 1e-4, "to {row},{col}: ({r}, {c}) is {off} off");
+            }
+        }
+    }
+
+    #[test]
+    fn line_matches_a_worked_example_both_ways() {
+        let grid = Grid::new(8, 8);
+        let cells = line(&grid, grid.idx(1, 0), grid.idx(3, 5));
+        assert_eq!(at(&grid, &cells), [(1, 0), (1, 1), (2, 2), (2, 3), (3, 4), (3, 5)]);
+        let back = line(&grid, grid.idx(3, 5), grid.idx(1, 0));
+        assert_eq!(back.len(), cells.len());
+        assert_eq!((back[0], back[5]), (cells[5], cells[0]));
+    }
+
+    #[test]
+    fn rect_is_outlined_or_filled() {
+        let grid = Grid::new(10, 10);
+        let (a, b) = (grid.idx(5, 6), grid.idx(2, 2));
+        let filled = rect(&grid, a, b, true);
+        assert_eq!(filled.len(), 4 * 5);
+        let outline = rect(&grid, a, b, false);
+        assert_eq!(outline.len(), 4 * 5 - 2 * 3);
+        assert!(outline.iter().all(|idx| filled.contains(idx)));
+        assert!(!outline.contains(&grid.idx(3, 3)) && !outline.contains(&grid.idx(4, 5)));
+        assert_eq!(rect(&grid, b, a, false), outline);
+
+        // One row or one cell has no inside to leave out.
+        assert_eq!(rect(&grid, grid.idx(1, 1), grid.idx(1, 4), false).len(), 4);
+        assert_eq!(rect(&grid, grid.idx(7, 7), grid.idx(7, 7), false), [grid.idx(7, 7)]);
+    }
+
+    #[test]
+    fn flood_stops_at_other_terrain() {
+        let grid = Grid::from_text(&[
+            "..mm#",
+            ".mm.#",
+            "ww.m#",
+            "m.###",
+        ].join("\n")).unwrap();
+        let sorted = |mut cells: Vec<usize>| { cells.sort_unstable(); cells };
+        let idx = |row, col| grid.idx(row, col);
+
+        // (1, 3) and (2, 3) are mud too, but only touch the patch diagonally.
+        assert_eq!(sorted(flood(&grid, idx(0, 2))), [idx(0, 2), idx(0, 3), idx(1, 1), idx(1, 2)]);
+        assert_eq!(sorted(flood(&grid, idx(1, 3))), [idx(1, 3)]);
+        assert_eq!(sorted(flood(&grid, idx(2, 0))), [idx(2, 0), idx(2, 1)]);
+        assert_eq!(sorted(flood(&grid, idx(0, 4))), [idx(0, 4), idx(1, 4), idx(2, 4), idx(3, 2), idx(3, 3), idx(3, 4)]);
+        assert_eq!(sorted(flood(&grid, idx(0, 0))), [idx(0, 0), idx(0, 1), idx(1, 0)]);
+        assert_eq!(grid.cell(idx(2, 0)).cost, COST_WATER);
+        assert_eq!(grid.cell(idx(3, 0)).cost, COST_MUD);
+    }
+
+    #[test]
+    fn stamp_clips_at_the_edges() {
+        let grid = Grid::new(10, 6);
+        let brush = Brush { radius: 2, ..Brush::default() };
+        assert_eq!(brush.stamp(&grid, &[grid.idx(3, 5)]).len(), 13);
+        assert_eq!(at(&grid, &brush.stamp(&grid, &[0])), [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (2, 0)]);
+        let corner = brush.stamp(&grid, &[grid.idx(5, 9)]);
+        assert_eq!(at(&grid, &corner), [(3, 9), (4, 8), (4, 9), (5, 7), (5, 8), (5, 9)]);
+
+        // Overlapping discs share cells once.
+        let pair = brush.stamp(&grid, &[grid.idx(3, 4), grid.idx(3, 5)]);
+        assert_eq!(pair.len(), 13 + 5);
+        assert!(pair.windows(2).all(|w| w[0] < w[1]));
+
+        let dot = Brush::default();
+        assert_eq!(dot.stamp(&grid, &[7, 3]), [7, 3]);
+    }
+}
@@ -0,0 +1,57 @@
+// src/compare.rs
+use crate::algorithms::{self, astar, hpa::Hierarchy, SearchData};
+use crate::grid::Grid;
+use crate::maze::trace::{Playback, Trace};
+use crate::stats::Stats;
+
+pub const MAX_PANES: usize = 4;
+
+/// What a fresh split view shows, pane by pane.
+pub const DEFAULT_ALGOS: [&str; MAX_PANES] = ["BFS", "Dijkstra", "A*", "DFS"];
+
+/// One algorithm's private copy of the grid in the split view.
+pub struct Pane {
+    pub algo: &'static str,
+    pub grid: Grid,
+    pub stats: Stats,
+    pub search: SearchData,
+    pub playback: Option<Playback>,
+}
+
+impl Pane {
+    /// Runs `algo` on a copy of `grid`, recorded for playback if `animate`.
+    pub fn run(algo: &'static str, grid: &Grid, hpa: &mut Option<Hierarchy>, astar: &astar::Params, animate: bool) -> Self {
+        let mut copy = grid.clone();
+        copy.reset_search_state();
+        let mut trace = if animate || algorithms::is_walker(algo) { Trace::recording() } else { Trace::off() };
+        let mut search = SearchData::default();
+        let stats = algorithms::run(algo, &mut copy, &mut trace, &mut search, hpa, astar);
+        let playback = trace.into_playback(&mut copy);
+        Self { algo, grid: copy, stats, search, playback }
+    }
+}
+
+/// Moves every pane on by the same number of algorithm steps, so a race
+/// between them is fair regardless of how much each records per step.
+pub fn tick(panes: &mut [Pane], steps: usize) {
+    for pane in panes.iter_mut() {
+        let Some(playback) = &mut pane.playback else { continue };
+        if !playback.paused {
+            playback.advance_steps(&mut pane.grid, steps);
+        }
+        if playback.is_done() {
+            if let Some(playback) = pane.playback.take() {
+                playback.finish(&mut pane.grid);
+            }
+        }
+    }
+}
+
+/// Screen rectangle `(x, y, w, h)` of pane `i` out of `count`: side by side
+/// for two, a 2x2 grid for three or four.
+pub fn layout(i: usize, count: usize, area_w: f32, area_h: f32) -> (f32, f32, f32, f32) {
+    let rows = if count > 2 { 2 } else { 1 };
+    let (w, h) = (area_w / 2.0, area_h / rows as f32);
+    ((i % 2) as f32 * w, (i / 2) as f32 * h, w, h)
+}
+
@@ -0,0 +1,399 @@
+// src/console.rs
+use macroquad::prelude::*;
+use crate::algorithms::{self, astar::Heuristic};
+use crate::api::{self, Command};
+use crate::grid::{COST_MUD, COST_WATER};
+use crate::maze::Generator;
+use crate::stats::Stats;
+use crate::AppState;
+
+/// Output lines kept for scrolling back.
+const MAX_LINES: usize = 200;
+const MAX_HISTORY: usize = 100;
+
+/// Every command with its usage line, as `help` lists them.
+const COMMANDS: [(&str, &str); 11] = [
+    ("size",  "size <width> <height>      empty grid, 3 to 1000 cells a side"),
+    ("seed",  "seed [n]                   seed used by gen; without n, show it"),
+    ("gen",   "gen <generator>            e.g. gen prims, gen bspdungeon"),
+    ("run",   "run [algorithm] [--heuristic manhattan|euclid|chebyshev] [--weight w]"),
+    ("fill",  "fill <wall|floor|mud|water> <x1> <y1> <x2> <y2>   rectangle, corners included"),
+    ("start", "start <x> <y>              move the start"),
+    ("end",   "end <x> <y>                move the end"),
+    ("save",  "save <file>                write the grid as text rows"),
+    ("load",  "load <file>                read grid text or a grid link"),
+    ("clear", "clear                      empty the console"),
+    ("help",  "help [command]"),
+];
+
+const FILLS: [&str; 4] = ["wall", "floor", "mud", "water"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Input,
+    Output,
+    Error,
+}
+
+/// Drop-down command line, toggled with the backtick key. Commands go
+/// through `api`, like the page API and the headless server.
+#[derive(Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub lines: Vec<(Kind, String)>,
+    history: Vec<String>,
+    /// Position in `history` while stepping through it with Up/Down.
+    browsing: Option<usize>,
+    /// Seed handed to `gen`; none leaves the generators' own sequence.
+    seed: Option<u64>,
+}
+
+impl Console {
+    /// While open, the console takes all keyboard input.
+    pub fn update(&mut self, state: &mut AppState) {
+        // macroquad keeps typed characters until read and hands them back
+        // newest first, so drain them every frame and put them in order.
+        let mut typed: Vec<char> = std::iter::from_fn(get_char_pressed).collect();
+        typed.reverse();
+        if is_key_pressed(KeyCode::GraveAccent) {
+            self.open = !self.open;
+            return;
+        }
+        if !self.open { return; }
+
+        self.input.extend(typed.into_iter().filter(|&ch| !ch.is_control() && ch != '`'));
+        if is_key_pressed(KeyCode::Backspace) { self.input.pop(); }
+        if is_key_pressed(KeyCode::Tab)       { self.complete(); }
+        if is_key_pressed(KeyCode::Up)        { self.browse(true); }
+        if is_key_pressed(KeyCode::Down)      { self.browse(false); }
+        if is_key_pressed(KeyCode::Escape)    { self.open = false; }
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            let line = std::mem::take(&mut self.input);
+            self.submit(state, &line);
+        }
+    }
+
+    /// Runs one command line, echoing it and its result.
+    pub fn submit(&mut self, state: &mut AppState, line: &str) {
+        let line = line.trim();
+        self.browsing = None;
+        if line.is_empty() { return; }
+        self.push(Kind::Input, format!("> {line}"));
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+            if self.history.len() > MAX_HISTORY { self.history.remove(0); }
+        }
+        match self.execute(state, line) {
+            Ok(text) if text.is_empty() => {}
+            Ok(text)                    => self.push(Kind::Output, text),
+            Err(message)                => self.push(Kind::Error, message),
+        }
+    }
+
+    fn push(&mut self, kind: Kind, text: String) {
+        self.lines.extend(text.lines().map(|line| (kind, line.to_string())));
+        let extra = self.lines.len().saturating_sub(MAX_LINES);
+        self.lines.drain(..extra);
+    }
+
+    fn execute(&mut self, state: &mut AppState, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = (words[0], &words[1..]);
+        let usage = || COMMANDS.iter().find(|(command, _)| *command == name).map_or("", |(_, usage)| usage);
+        let number = |i: usize| -> Result<usize, String> {
+            args.get(i).and_then(|arg| arg.parse().ok()).ok_or_else(|| format!("usage: {}", usage()))
+        };
+
+        match name {
+            "size" => {
+                let (width, height) = (number(0)?, number(1)?);
+                api::apply(state, Command::Create(width, height))?;
+                Ok(format!("Grid is now {width}x{height}"))
+            }
+            "seed" => match args {
+                []  => Ok(self.seed.map_or("No seed set; gen continues the current sequence".to_string(), |seed| format!("Seed {seed}"))),
+                [n] => {
+                    self.seed = Some(n.parse().map_err(|_| format!("usage: {}", usage()))?);
+                    Ok(format!("gen will use seed {n}"))
+                }
+                _   => Err(format!("usage: {}", usage())),
+            },
+            "gen" => {
+                if args.is_empty() { return Err(format!("usage: {}", usage())); }
+                let generator = api::generator(&args.join(" "))?;
+                api::apply(state, Command::Generate(generator, self.seed))?;
+                Ok(format!("Generated {}", generator.name()))
+            }
+            "run" => {
+                let (mut algo, mut params) = (None, state.astar);
+                let mut rest = args.iter();
+                while let Some(&arg) = rest.next() {
+                    match arg {
+                        "--heuristic" => {
+                            params.heuristic = api::heuristic(rest.next().ok_or("--heuristic needs a name")?)?;
+                        }
+                        "--weight" => {
+                            let weight = rest.next().and_then(|w| w.parse().ok()).ok_or("--weight needs a number")?;
+                            params.weight = api::weight(weight)?;
+                        }
+                        option if option.starts_with("--") => return Err(format!("unknown option {option}")),
+                        name if algo.is_none() => algo = Some(api::algorithm(name)?.to_string()),
+                        _ => return Err("run takes one algorithm".to_string()),
+                    }
+                }
+                state.astar = params;
+                api::apply(state, Command::Run(algo))?;
+                Ok(summary(state))
+            }
+            "fill" => {
+                let kind = args.first().copied().unwrap_or_default();
+                let (x1, y1, x2, y2) = (number(1)?, number(2)?, number(3)?, number(4)?);
+                let (width, height) = (state.grid.width, state.grid.height);
+                if x1.max(x2) >= width || y1.max(y2) >= height {
+                    return Err(format!("the grid is {width}x{height}; x runs 0 to {}, y 0 to {}", width - 1, height - 1));
+                }
+                let cells: Vec<(usize, usize)> = (y1.min(y2)..=y1.max(y2))
+                    .flat_map(|row| (x1.min(x2)..=x1.max(x2)).map(move |col| (row, col)))
+                    .collect();
+                let count = cells.len();
+                let command = match kind {
+                    "wall"  => Command::Walls(cells, true),
+                    "floor" => Command::Walls(cells, false),
+                    "mud"   => Command::Terrain(cells, COST_MUD),
+                    "water" => Command::Terrain(cells, COST_WATER),
+                    _       => return Err(format!("usage: {}", usage())),
+                };
+                api::apply(state, command)?;
+                Ok(format!("Filled {count} cells with {kind}"))
+            }
+            "start" | "end" => {
+                let (col, row) = (number(0)?, number(1)?);
+                api::apply(state, if name == "start" { Command::SetStart(row, col) } else { Command::SetEnd(row, col) })?;
+                Ok(format!("Moved the {name} to x {col}, y {row}"))
+            }
+            "save" => {
+                if args.is_empty() { return Err(format!("usage: {}", usage())); }
+                let path = args.join(" ");
+                std::fs::write(&path, state.grid.to_text()).map_err(|e| format!("could not write {path}: {e}"))?;
+                Ok(format!("Saved {}x{} grid to {path}", state.grid.width, state.grid.height))
+            }
+            "load" => {
+                if args.is_empty() { return Err(format!("usage: {}", usage())); }
+                let path = args.join(" ");
+                let text = std::fs::read_to_string(&path).map_err(|e| format!("could not read {path}: {e}"))?;
+                api::apply(state, Command::Load(text))?;
+                Ok(format!("Loaded {}x{} grid from {path}", state.grid.width, state.grid.height))
+            }
+            "clear" => {
+                self.lines.clear();
+                Ok(String::new())
+            }
+            "help" => match args.first() {
+                Some(command) => COMMANDS.iter().find(|(name, _)| name == command)
+                    .map(|(_, usage)| usage.to_string())
+                    .ok_or_else(|| format!("no command '{command}'")),
+                None => Ok(COMMANDS.iter().map(|(_, usage)| *usage).collect::<Vec<_>>().join("\n")),
+            },
+            _ => Err(format!("unknown command '{name}', try help")),
+        }
+    }
+
+    /// Completes the word under the cursor, or lists the choices when more
+    /// than one fits and they share nothing more.
+    fn complete(&mut self) {
+        let split = self.input.rfind(' ').map_or(0, |i| i + 1);
+        let (head, partial) = (self.input[..split].to_string(), self.input[split..].to_lowercase());
+        let before: Vec<&str> = head.split_whitespace().collect();
+        let options: Vec<String> = candidates(&before).into_iter().filter(|option| option.starts_with(&partial)).collect();
+        match options.as_slice() {
+            []     => {}
+            [only] => self.input = format!("{head}{only} "),
+            _      => {
+                let common = options.iter().skip(1).fold(options[0].as_str(), |common, option| {
+                    let same = common.bytes().zip(option.bytes()).take_while(|(a, b)| a == b).count();
+                    &common[..same]
+                });
+                if common.len() > partial.len() {
+                    self.input = format!("{head}{common}");
+                } else {
+                    self.push(Kind::Output, options.join("  "));
+                }
+            }
+        }
+    }
+
+    fn browse(&mut self, back: bool) {
+        if self.history.is_empty() { return; }
+        self.browsing = match (self.browsing, back) {
+            (None, true)     => Some(self.history.len() - 1),
+            (None, false)    => None,
+            (Some(i), true)  => Some(i.saturating_sub(1)),
+            (Some(i), false) => (i + 1 < self.history.len()).then_some(i + 1),
+        };
+        self.input = self.browsing.map_or(String::new(), |i| self.history[i].clone());
+    }
+}
+
+/// What can follow the words already typed.
+fn candidates(before: &[&str]) -> Vec<String> {
+    match before {
+        [] | ["help"]              => COMMANDS.iter().map(|(name, _)| name.to_string()).collect(),
+        ["gen"]                    => keys(Generator::ALL.iter().map(|g| g.name())),
+        ["run", .., "--heuristic"] => keys(Heuristic::ALL.iter().map(|h| h.name())),
+        ["run", .., "--weight"]    => Vec::new(),
+        ["run", ..]                => {
+            let mut options = keys(algorithms::NAMES);
+            options.extend(["--heuristic".to_string(), "--weight".to_string()]);
+            options
+        }
+        ["fill"]                   => keys(FILLS),
+        _                          => Vec::new(),
+    }
+}
+
+fn keys<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    names.into_iter().map(api::key).collect()
+}
+
+/// One line on the run just made.
+fn summary(state: &AppState) -> String {
+    let algo = state.last_algo.unwrap_or_default();
+    let Stats { path_found, path_length, path_cost, nodes_explored, execution_ms, .. } = state.stats;
+    let mut text = if path_found {
+        format!("{algo}: path {path_length}, cost {path_cost}, {nodes_explored} explored, {execution_ms:.2} ms")
+    } else {
+        format!("{algo}: no path, {nodes_explored} explored, {execution_ms:.2} ms")
+    };
+    if algo == "A*" {
+        text += &format!(" ({}, weight {})", state.astar.heuristic.name(), state.astar.weight);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> (Console, AppState) {
+        (Console::default(), AppState::new())
+    }
+
+    fn open_cells(state: &AppState) -> Vec<usize> {
+        (0..state.grid.width * state.grid.height).filter(|&i| !state.grid.cell(i).is_wall).collect()
+    }
+
+    #[test]
+    fn fill_takes_corners_in_either_order() {
+        let (mut console, mut state) = session();
+        assert_eq!(console.execute(&mut state, "fill wall 5 3 2 1").unwrap(), "Filled 12 cells with wall");
+        let walls: Vec<usize> = (0..60 * 40).filter(|&i| state.grid.cell(i).is_wall).collect();
+        let expected: Vec<usize> = (1..=3).flat_map(|row| (2..=5).map(move |col| row * 60 + col)).collect();
+        assert_eq!(walls, expected);
+
+        console.execute(&mut state, "fill floor 2 1 5 3").unwrap();
+        assert_eq!(open_cells(&state).len(), 60 * 40);
+        console.execute(&mut state, "fill mud 7 7 7 7").unwrap();
+        assert_eq!(state.grid.cell(7 * 60 + 7).cost, COST_MUD);
+    }
+
+    #[test]
+    fn fill_checks_bounds_and_kind_before_changing_anything() {
+        let (mut console, mut state) = session();
+        let error = console.execute(&mut state, "fill wall 0 0 60 5").unwrap_err();
+        assert_eq!(error, "the grid is 60x40; x runs 0 to 59, y 0 to 39");
+        assert!(console.execute(&mut state, "fill wall 0 0 5 40").is_err());
+        assert!(console.execute(&mut state, "fill lava 0 0 5 5").unwrap_err().starts_with("usage: fill"));
+        assert!(console.execute(&mut state, "fill wall 0 0 5").unwrap_err().starts_with("usage: fill"));
+        assert!(console.execute(&mut state, "fill wall -1 0 5 5").is_err());
+        assert_eq!(open_cells(&state).len(), 60 * 40);
+    }
+
+    #[test]
+    fn run_reads_heuristic_and_weight() {
+        let (mut console, mut state) = session();
+        let text = console.execute(&mut state, "run a* --heuristic chebyshev --weight 2.5").unwrap();
+        assert_eq!(state.last_algo, Some("A*"));
+        assert_eq!(state.astar.heuristic.name(), "Chebyshev");
+        assert_eq!(state.astar.weight, 2.5);
+        assert!(text.starts_with("A*: path ") && text.ends_with("(Chebyshev, weight 2.5)"), "{text}");
+    }
+
+    #[test]
+    fn run_rejects_bad_options_and_keeps_the_old_ones() {
+        let (mut console, mut state) = session();
+        let cases = [
+            ("--heuristic", "--heuristic needs a name"),
+            ("--heuristic taxicab", "unknown heuristic 'taxicab', expected one of Manhattan, Euclid, Chebyshev"),
+            ("--weight", "--weight needs a number"),
+            ("--weight heavy", "--weight needs a number"),
+            ("--weight 11", "weight must be between 0 and 10"),
+            ("--weight -1", "weight must be between 0 and 10"),
+            ("--fast", "unknown option --fast"),
+            ("bfs", "run takes one algorithm"),
+        ];
+        // A good option before the bad one is not kept either.
+        for (line, expected) in cases {
+            let line = format!("run a* --heuristic euclid {line}");
+            assert_eq!(console.execute(&mut state, &line).unwrap_err(), expected, "{line}");
+            assert_eq!(state.astar.heuristic.name(), "Manhattan", "{line}");
+            assert_eq!(state.astar.weight, 1.0, "{line}");
+            assert_eq!(state.last_algo, None, "{line}");
+        }
+        assert!(console.execute(&mut state, "run quicksort").unwrap_err().starts_with("unknown algorithm 'quicksort'"));
+    }
+
+    #[test]
+    fn seed_is_shown_set_and_checked() {
+        let (mut console, mut state) = session();
+        assert_eq!(console.execute(&mut state, "seed").unwrap(), "No seed set; gen continues the current sequence");
+        assert_eq!(console.execute(&mut state, "seed 42").unwrap(), "gen will use seed 42");
+        assert_eq!(console.execute(&mut state, "seed").unwrap(), "Seed 42");
+        assert!(console.execute(&mut state, "seed x").unwrap_err().starts_with("usage: seed"));
+        assert!(console.execute(&mut state, "seed 1 2").unwrap_err().starts_with("usage: seed"));
+        assert_eq!(console.seed, Some(42));
+
+        // The same seed gives the same maze.
+        console.execute(&mut state, "gen kruskal").unwrap();
+        let first = state.grid.to_text();
+        console.execute(&mut state, "gen kruskal").unwrap();
+        assert_eq!(state.grid.to_text(), first);
+    }
+
+    #[test]
+    fn candidates_follow_the_words_typed() {
+        let names: Vec<String> = COMMANDS.iter().map(|(name, _)| name.to_string()).collect();
+        assert_eq!(candidates(&[]), names);
+        assert_eq!(candidates(&["help"]), names);
+        assert_eq!(candidates(&["fill"]), ["wall", "floor", "mud", "water"]);
+        assert_eq!(candidates(&["run", "astar", "--heuristic"]), ["manhattan", "euclid", "chebyshev"]);
+        assert!(candidates(&["run", "--weight"]).is_empty());
+        let run = candidates(&["run"]);
+        assert!(run.contains(&"astar".to_string()) && run.contains(&"wallr".to_string()) && run.ends_with(&["--heuristic".to_string(), "--weight".to_string()]));
+        assert!(candidates(&["gen"]).contains(&"bspdungeon".to_string()));
+        assert!(candidates(&["fill", "wall"]).is_empty());
+        assert!(candidates(&["size"]).is_empty());
+    }
+
+    #[test]
+    fn complete_fills_in_or_lists_choices() {
+        let mut console = Console::default();
+        let mut completed = |input: &str| {
+            console.input = input.to_string();
+            console.complete();
+            console.input.clone()
+        };
+        assert_eq!(completed("he"), "help ");
+        assert_eq!(completed("fill w"), "fill wa");
+        assert_eq!(completed("fill wal"), "fill wall ");
+        assert_eq!(completed("run a* --heuristic EU"), "run a* --heuristic euclid ");
+        assert_eq!(completed("gen hun"), "gen huntkill ");
+        assert_eq!(completed("zzz"), "zzz");
+
+        // Nothing in common past what is typed: the choices are listed instead.
+        console.input = "s".to_string();
+        console.complete();
+        assert_eq!(console.input, "s");
+        assert_eq!(console.lines.last().unwrap().1, "size  seed  start  save");
+    }
+}
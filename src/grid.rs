@@ -1,14 +1,20 @@
 // src/grid.rs
+use std::sync::atomic::{AtomicU64, Ordering};
 
 pub const COST_NORMAL: u16 = 1;
 pub const COST_MUD: u16 = 3;
 pub const COST_WATER: u16 = 5;
 
+static NEXT_VERSION: AtomicU64 = AtomicU64::new(1);
+
 #[derive(Clone, Copy, Default)]
 pub struct Cell {
     pub is_wall: bool,
     pub is_start: bool,
     pub is_end: bool,
+    /// Reached by a search but not expanded yet.
+    pub is_open: bool,
+    /// Expanded by a search (the closed set).
     pub is_visited: bool,
     pub is_path: bool,
     pub cost: u16,
@@ -20,10 +26,17 @@ impl Cell {
     }
 }
 
+#[derive(Clone)]
 pub struct Grid {
     pub width: usize,
     pub height: usize,
     cells: Vec<Cell>,
+    /// Set whenever a wall or terrain cost may have changed. HPA* clears it
+    /// once its abstraction has caught up.
+    edited: bool,
+    /// Stamp for caches of anything drawn from the cells; see `version`.
+    /// 0 means changed and not numbered yet.
+    version: std::cell::Cell<u64>,
 }
 
 impl Grid {
@@ -32,6 +45,8 @@ impl Grid {
             width,
             height,
             cells: vec![Cell::new(); width * height],
+            edited: true,
+            version: std::cell::Cell::new(0),
         }
     }
 
@@ -44,39 +59,114 @@ impl Grid {
     }
 
     pub fn cell_mut(&mut self, idx: usize) -> &mut Cell {
+        self.version.set(0);
         &mut self.cells[idx]
     }
 
     pub fn set_wall(&mut self, idx: usize, value: bool) {
         self.cells[idx].is_wall = value;
         self.cells[idx].cost = if value { 0 } else { COST_NORMAL };
+        self.edited = true;
+        self.version.set(0);
     }
 
     pub fn set_start(&mut self, idx: usize) {
         for cell in self.cells.iter_mut() { cell.is_start = false; }
         self.cells[idx].is_start = true;
+        self.version.set(0);
     }
 
     pub fn set_end(&mut self, idx: usize) {
         for cell in self.cells.iter_mut() { cell.is_end = false; }
         self.cells[idx].is_end = true;
+        self.version.set(0);
+    }
+
+    pub fn start(&self) -> Option<usize> {
+        self.cells.iter().position(|cell| cell.is_start)
+    }
+
+    pub fn end(&self) -> Option<usize> {
+        self.cells.iter().position(|cell| cell.is_end)
     }
 
     pub fn set_terrain(&mut self, idx: usize, cost: u16) {
         if !self.cells[idx].is_wall {
             self.cells[idx].cost = cost;
+            self.edited = true;
+            self.version.set(0);
+        }
+    }
+
+    /// Equal for two grids only if their cells are equal, so anything drawn
+    /// from the cells can be cached against it. Numbered on first use after
+    /// a change, which keeps writes to the cells cheap.
+    pub fn version(&self) -> u64 {
+        if self.version.get() == 0 {
+            self.version.set(NEXT_VERSION.fetch_add(1, Ordering::Relaxed));
         }
+        self.version.get()
+    }
+
+    /// Whether walls or terrain changed since the last call.
+    pub fn take_edited(&mut self) -> bool {
+        std::mem::take(&mut self.edited)
     }
 
     pub fn reset_search_state(&mut self) {
         for cell in self.cells.iter_mut() {
+            cell.is_open = false;
             cell.is_visited = false;
             cell.is_path = false;
         }
+        self.version.set(0);
     }
 
     pub fn reset_all(&mut self) {
         for cell in self.cells.iter_mut() { *cell = Cell::new(); }
+        self.edited = true;
+        self.version.set(0);
+    }
+
+    /// One line per row, one character per cell: `#` wall, `.` floor,
+    /// `m` mud, `w` water, `S` start, `E` end. Endpoints lose their terrain.
+    pub fn to_text(&self) -> String {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width) {
+            text.extend(row.iter().map(|cell| {
+                if cell.is_start                 { 'S' }
+                else if cell.is_end              { 'E' }
+                else if cell.is_wall             { '#' }
+                else if cell.cost == COST_MUD    { 'm' }
+                else if cell.cost == COST_WATER  { 'w' }
+                else                             { '.' }
+            }));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Reads what `to_text` wrote. Rows must all be the same length.
+    pub fn from_text(text: &str) -> Option<Grid> {
+        let rows: Vec<&str> = text.lines().map(str::trim_end).filter(|row| !row.is_empty()).collect();
+        let width = rows.first()?.chars().count();
+        if rows.iter().any(|row| row.chars().count() != width) { return None; }
+        let mut grid = Grid::new(width, rows.len());
+        for (r, row) in rows.iter().enumerate() {
+            for (c, ch) in row.chars().enumerate() {
+                let idx = grid.idx(r, c);
+                match ch {
+                    '#' => grid.set_wall(idx, true),
+                    'm' => grid.set_terrain(idx, COST_MUD),
+                    'w' => grid.set_terrain(idx, COST_WATER),
+                    'S' => grid.set_start(idx),
+                    'E' => grid.set_end(idx),
+                    '.' => {}
+                    _   => return None,
+                }
+            }
+        }
+        Some(grid)
     }
 
     pub fn neighbors(&self, idx: usize) -> Vec<usize> {
@@ -0,0 +1,60 @@
+// src/heatmap.rs
+use macroquad::prelude::*;
+use crate::algorithms::SearchData;
+
+/// Which number from the last search the overlay colours cells by.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Heatmap {
+    Off,
+    /// Distance (or cost) from the start.
+    G,
+    H,
+    F,
+    /// Order of expansion.
+    Order,
+}
+
+impl Heatmap {
+    pub const ALL: [Heatmap; 5] = [Heatmap::Off, Heatmap::G, Heatmap::H, Heatmap::F, Heatmap::Order];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Heatmap::Off   => "Off",
+            Heatmap::G     => "g (dist)",
+            Heatmap::H     => "h",
+            Heatmap::F     => "f = g+h",
+            Heatmap::Order => "Order",
+        }
+    }
+
+    pub fn value(self, data: &SearchData, idx: usize) -> Option<u32> {
+        let value = match self {
+            Heatmap::Off   => return None,
+            Heatmap::G     => *data.g.get(idx)?,
+            Heatmap::H     => *data.h.get(idx)?,
+            Heatmap::F     => if idx < data.g.len() { data.f(idx) } else { u32::MAX },
+            Heatmap::Order => *data.order.get(idx)?,
+        };
+        (value != u32::MAX).then_some(value)
+    }
+
+    /// Smallest and largest value over all cells, if any cell has one.
+    pub fn range(self, data: &SearchData) -> Option<(u32, u32)> {
+        (0..data.g.len())
+            .filter_map(|idx| self.value(data, idx))
+            .fold(None, |range, v| match range {
+                None => Some((v, v)),
+                Some((lo, hi)) => Some((lo.min(v), hi.max(v))),
+            })
+    }
+}
+
+/// Colour for `t` in 0..=1 along a cold-to-hot ramp of `stops`.
+pub fn ramp(stops: &[Color; 6], t: f32) -> Color {
+    let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let i = (scaled as usize).min(stops.len() - 2);
+    let frac = scaled - i as f32;
+    let (a, b) = (stops[i], stops[i + 1]);
+    let mix = |x: f32, y: f32| x + (y - x) * frac;
+    Color::new(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), 1.0)
+}
@@ -0,0 +1,342 @@
+// src/json.rs
+use std::fmt;
+
+/// Just enough JSON for the server protocol: requests are parsed into this,
+/// replies are written with `format!` and `quote`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Keys in the order they were written.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self { Value::String(text) => Some(text), _ => None }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self { Value::Bool(value) => Some(*value), _ => None }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self { Value::Array(items) => Some(items), _ => None }
+    }
+
+    /// Whole, non-negative numbers only.
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 && *n <= usize::MAX as f64 => Some(*n as usize),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_usize().map(|n| n as u64)
+    }
+}
+
+/// Compact JSON, e.g. for echoing a request's `id` back.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null          => write!(f, "null"),
+            Value::Bool(value)   => write!(f, "{value}"),
+            Value::Number(n) if n.is_finite() => write!(f, "{n}"),
+            Value::Number(_)     => write!(f, "null"),
+            Value::String(text)  => write!(f, "{}", quote(text)),
+            Value::Array(items)  => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 { write!(f, ",")?; }
+                    write!(f, "{item}")?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 { write!(f, ",")?; }
+                    write!(f, "{}:{value}", quote(key))?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// `text` as a JSON string literal, quotes included.
+pub fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c    => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Parses one complete JSON document; trailing text is an error.
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut parser = Parser { bytes: text.as_bytes(), at: 0 };
+    let value = parser.value(0)?;
+    parser.skip_space();
+    if parser.at != parser.bytes.len() {
+        return Err(format!("unexpected text at byte {}", parser.at));
+    }
+    Ok(value)
+}
+
+/// Nesting deeper than this is refused rather than risking the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn skip_space(&mut self) {
+        while self.bytes.get(self.at).is_some_and(u8::is_ascii_whitespace) { self.at += 1; }
+    }
+
+    fn error(&self, what: &str) -> String {
+        format!("{what} at byte {}", self.at)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_space();
+        if self.bytes.get(self.at) != Some(&byte) {
+            return Err(self.error(&format!("expected '{}'", byte as char)));
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        if !self.bytes[self.at..].starts_with(word.as_bytes()) { return Err(self.error("unknown literal")); }
+        self.at += word.len();
+        Ok(value)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH { return Err(self.error("nested too deeply")); }
+        self.skip_space();
+        match self.bytes.get(self.at) {
+            None       => Err(self.error("unexpected end")),
+            Some(b'n') => self.literal("null", Value::Null),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => {
+                self.at += 1;
+                let mut items = Vec::new();
+                self.skip_space();
+                if self.bytes.get(self.at) == Some(&b']') { self.at += 1; return Ok(Value::Array(items)); }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    self.skip_space();
+                    match self.bytes.get(self.at) {
+                        Some(b',') => self.at += 1,
+                        Some(b']') => { self.at += 1; return Ok(Value::Array(items)); }
+                        _          => return Err(self.error("expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.at += 1;
+                let mut fields = Vec::new();
+                self.skip_space();
+                if self.bytes.get(self.at) == Some(&b'}') { self.at += 1; return Ok(Value::Object(fields)); }
+                loop {
+                    self.skip_space();
+                    if self.bytes.get(self.at) != Some(&b'"') { return Err(self.error("expected a key")); }
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key, self.value(depth + 1)?));
+                    self.skip_space();
+                    match self.bytes.get(self.at) {
+                        Some(b',') => self.at += 1,
+                        Some(b'}') => { self.at += 1; return Ok(Value::Object(fields)); }
+                        _          => return Err(self.error("expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(_) => self.number(),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.at;
+        while self.bytes.get(self.at).is_some_and(|b| matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')) {
+            self.at += 1;
+        }
+        std::str::from_utf8(&self.bytes[start..self.at]).ok()
+            .and_then(|text| text.parse::<f64>().ok())
+            .filter(|n| n.is_finite())
+            .map(Value::Number)
+            .ok_or_else(|| { self.at = start; self.error("bad value") })
+    }
+
+    /// Reads a string literal; the cursor is on its opening quote.
+    fn string(&mut self) -> Result<String, String> {
+        self.at += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let Some(&byte) = self.bytes.get(self.at) else { return Err(self.error("unterminated string")) };
+            self.at += 1;
+            match byte {
+                b'"'  => break,
+                b'\\' => {
+                    let Some(&escape) = self.bytes.get(self.at) else { return Err(self.error("unterminated string")) };
+                    self.at += 1;
+                    let ch = match escape {
+                        b'"'  => '"',
+                        b'\\' => '\\',
+                        b'/'  => '/',
+                        b'b'  => '\u{8}',
+                        b'f'  => '\u{c}',
+                        b'n'  => '\n',
+                        b'r'  => '\r',
+                        b't'  => '\t',
+                        b'u'  => self.unicode_escape()?,
+                        _     => return Err(self.error("bad escape")),
+                    };
+                    bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        // The input was a &str and escapes add whole characters, so this holds.
+        String::from_utf8(bytes).map_err(|_| self.error("invalid UTF-8"))
+    }
+
+    /// `\uXXXX`, joining surrogate pairs; a lone surrogate becomes U+FFFD.
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        if (0xd800..0xdc00).contains(&high) && self.bytes[self.at..].starts_with(b"\\u") {
+            let at = self.at;
+            self.at += 2;
+            let low = self.hex4()?;
+            if (0xdc00..0xe000).contains(&low) {
+                return Ok(char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)).unwrap_or('\u{fffd}'));
+            }
+            self.at = at;
+        }
+        Ok(char::from_u32(high).unwrap_or('\u{fffd}'))
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits = self.bytes.get(self.at..self.at + 4).ok_or_else(|| self.error("short \\u escape"))?;
+        let value = Some(digits).filter(|digits| digits.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|text| u32::from_str_radix(text, 16).ok())
+            .ok_or_else(|| self.error("bad \\u escape"))?;
+        self.at += 4;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(n: f64) -> Value {
+        Value::Number(n)
+    }
+
+    #[test]
+    fn parses_nested_objects_and_arrays() {
+        let value = parse(r#" {"cmd": "walls", "cells": [[1, 2], [3, 4]], "wall": false, "meta": {"id": null, "depth": [[], {}]}} "#).unwrap();
+        assert_eq!(value.get("cmd").and_then(Value::as_str), Some("walls"));
+        assert_eq!(value.get("wall").and_then(Value::as_bool), Some(false));
+        let cells = value.get("cells").and_then(Value::as_array).unwrap();
+        assert_eq!(cells, [Value::Array(vec![number(1.0), number(2.0)]), Value::Array(vec![number(3.0), number(4.0)])]);
+        let meta = value.get("meta").unwrap();
+        assert_eq!(meta.get("id"), Some(&Value::Null));
+        assert_eq!(meta.get("depth"), Some(&Value::Array(vec![Value::Array(Vec::new()), Value::Object(Vec::new())])));
+    }
+
+    #[test]
+    fn parses_numbers() {
+        assert_eq!(parse("-12.5e1"), Ok(number(-125.0)));
+        assert_eq!(parse("42").unwrap().as_usize(), Some(42));
+        assert_eq!(parse("4.5").unwrap().as_usize(), None);
+        assert_eq!(parse("-1").unwrap().as_usize(), None);
+        assert!(parse("1e999").is_err());
+        assert!(parse("--").is_err());
+    }
+
+    #[test]
+    fn decodes_unicode_escapes() {
+        assert_eq!(parse(r#""\u0041\u00e9\u4E2D""#), Ok(Value::String("Aé中".to_string())));
+        assert_eq!(parse(r#""\ud83d\ude00""#), Ok(Value::String("😀".to_string())));
+        assert_eq!(parse(r#""\ud83d x""#), Ok(Value::String("\u{fffd} x".to_string())));
+        assert_eq!(parse(r#""\ude00""#), Ok(Value::String("\u{fffd}".to_string())));
+        assert_eq!(parse(r#""\ud83dA""#), Ok(Value::String("\u{fffd}A".to_string())));
+        assert!(parse(r#""\u12""#).is_err());
+        assert!(parse(r#""\uzzzz""#).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_literals() {
+        for text in ["nul", "tru", "falsy", "True", "undefined"] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn rejects_unterminated_strings() {
+        for text in [r#""abc"#, r#""abc\"#, r#"{"key"#, r#"["a", "b"#] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH + 1)).is_ok());
+        assert!(parse(&nested(MAX_DEPTH + 2)).is_err());
+        assert!(parse(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn rejects_trailing_text() {
+        for text in ["{} {}", "[1] x", "true false", r#""a" "b""#, "1,"] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+        assert!(parse("  [1]  \n").is_ok());
+    }
+
+    #[test]
+    fn quote_round_trips_through_parse() {
+        for text in ["", "plain", "say \"hi\"", "back\\slash", "line\nbreak\r\ttab", "\u{0}\u{1f}\u{7f}", "é中😀"] {
+            assert_eq!(parse(&quote(text)), Ok(Value::String(text.to_string())), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn display_round_trips_through_parse() {
+        let value = parse(r#"{"id":"a\"b","cells":[[0,1],[2.5,-3]],"ok":true,"none":null}"#).unwrap();
+        assert_eq!(parse(&value.to_string()), Ok(value));
+    }
+}
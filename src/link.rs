@@ -0,0 +1,293 @@
+// src/link.rs
+use crate::algorithms;
+use crate::grid::{Grid, COST_MUD, COST_WATER};
+
+/// Bumped whenever the layout below changes, so old links fail cleanly.
+const VERSION: u8 = 1;
+
+/// What goes before the code in a URL fragment: `...#g=<code>`.
+pub const FRAGMENT_PREFIX: &str = "g=";
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// Two-bit cell classes in the run stream.
+const FLOOR: u64 = 0;
+const WALL: u64 = 1;
+const MUD: u64 = 2;
+const WATER: u64 = 3;
+
+/// Packs the grid and the chosen algorithm into URL-safe text.
+///
+/// Layout before base64: version byte, width, height, algorithm (0 for
+/// none, else its index in `NAMES` + 1), start + 1, end + 1 (0 for none),
+/// then runs of equal cells as `length << 2 | class`. Every number except
+/// the version and algorithm is a LEB128 varint.
+pub fn encode(grid: &Grid, algo: Option<&str>) -> String {
+    let mut bytes = vec![VERSION];
+    push_varint(&mut bytes, grid.width as u64);
+    push_varint(&mut bytes, grid.height as u64);
+    let algo_id = algo.and_then(|a| algorithms::NAMES.iter().position(|&name| name == a));
+    bytes.push(algo_id.map_or(0, |i| i as u8 + 1));
+    push_varint(&mut bytes, grid.start().map_or(0, |i| i as u64 + 1));
+    push_varint(&mut bytes, grid.end().map_or(0, |i| i as u64 + 1));
+
+    let size = grid.width * grid.height;
+    let mut i = 0;
+    while i < size {
+        let class = class_of(grid, i);
+        let run = (i..size).take_while(|&j| class_of(grid, j) == class).count();
+        push_varint(&mut bytes, (run as u64) << 2 | class);
+        i += run;
+    }
+    to_base64(&bytes)
+}
+
+/// Reverses `encode`. Takes the bare code, a `g=` fragment or a whole URL.
+/// Anything truncated, out of size range or inconsistent gives `None`.
+pub fn decode(text: &str) -> Option<(Grid, Option<&'static str>)> {
+    let code = text.trim();
+    let code = code.rsplit_once(FRAGMENT_PREFIX).map_or(code, |(_, code)| code);
+    let bytes = from_base64(code)?;
+    let mut reader = Reader { bytes: &bytes, at: 0 };
+
+    if reader.byte()? != VERSION { return None; }
+    let width = usize::try_from(reader.varint()?).ok()?;
+    let height = usize::try_from(reader.varint()?).ok()?;
+    // Only sizes the app makes itself, 3 to `LARGE_GRID_SIZE` a side.
+    if !crate::fits(width, height) { return None; }
+    let size = width * height;
+    let algo = match reader.byte()? {
+        0  => None,
+        id => Some(*algorithms::NAMES.get(id as usize - 1)?),
+    };
+    let start = reader.varint()?;
+    let end = reader.varint()?;
+
+    let mut grid = Grid::new(width, height);
+    let mut i = 0;
+    while i < size {
+        let run = reader.varint()?;
+        let length = usize::try_from(run >> 2).ok()?;
+        if length == 0 || length > size - i { return None; }
+        for idx in i..i + length {
+            match run & 3 {
+                WALL  => grid.set_wall(idx, true),
+                MUD   => grid.set_terrain(idx, COST_MUD),
+                WATER => grid.set_terrain(idx, COST_WATER),
+                _     => {}
+            }
+        }
+        i += length;
+    }
+    if reader.at != bytes.len() { return None; }
+
+    let cell_at = |value: u64| usize::try_from(value - 1).ok().filter(|&idx| idx < size);
+    if start > 0 { grid.set_start(cell_at(start)?); }
+    if end > 0   { grid.set_end(cell_at(end)?); }
+    Some((grid, algo))
+}
+
+fn class_of(grid: &Grid, idx: usize) -> u64 {
+    let cell = grid.cell(idx);
+    if cell.is_wall                 { WALL }
+    else if cell.cost == COST_MUD   { MUD }
+    else if cell.cost == COST_WATER { WATER }
+    else                            { FLOOR }
+}
+
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.at)?;
+        self.at += 1;
+        Some(byte)
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 { return Some(value); }
+        }
+        None
+    }
+}
+
+/// URL-safe alphabet, no padding.
+fn to_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            text.push(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    text
+}
+
+fn from_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for ch in text.bytes() {
+        let value = BASE64.iter().position(|&c| c == ch)? as u32;
+        bits = bits << 6 | value;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// The code in the page's URL fragment, if there is one.
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub fn from_page() -> Option<String> {
+    let hash = crate::web::get(&crate::web::global("location")?, "hash")?.as_string()?;
+    hash.strip_prefix('#')?.strip_prefix(FRAGMENT_PREFIX).map(str::to_string)
+}
+
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+pub fn from_page() -> Option<String> {
+    None
+}
+
+/// Puts `code` in the page's URL fragment, without a new history entry, and
+/// returns the whole link. Without a page there is only the fragment.
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub fn publish(code: &str) -> String {
+    use js_sys::wasm_bindgen::JsValue;
+    use crate::web;
+
+    let fragment = format!("#{FRAGMENT_PREFIX}{code}");
+    if let Some(history) = web::global("history") {
+        web::call(&history, "replaceState", &[JsValue::NULL, JsValue::from_str(""), JsValue::from_str(&fragment)]);
+    }
+    web::global("location")
+        .and_then(|location| web::get(&location, "href"))
+        .and_then(|href| href.as_string())
+        .unwrap_or(fragment)
+}
+
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+pub fn publish(code: &str) -> String {
+    format!("#{FRAGMENT_PREFIX}{code}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grid with every cell class, laid out in a pattern with long and
+    /// short runs.
+    fn sample(width: usize, height: usize) -> Grid {
+        let mut grid = Grid::new(width, height);
+        for idx in 0..width * height {
+            match (idx * 7 + idx / 5) % 11 {
+                0..=2 => grid.set_wall(idx, true),
+                3     => grid.set_terrain(idx, COST_MUD),
+                4     => grid.set_terrain(idx, COST_WATER),
+                _     => {}
+            }
+        }
+        grid.set_start(0);
+        grid.set_end(width * height - 1);
+        grid
+    }
+
+    fn assert_same(a: &Grid, b: &Grid) {
+        assert_eq!((a.width, a.height), (b.width, b.height));
+        for idx in 0..a.width * a.height {
+            let (x, y) = (a.cell(idx), b.cell(idx));
+            assert_eq!((x.is_wall, x.cost, x.is_start, x.is_end), (y.is_wall, y.cost, y.is_start, y.is_end), "cell {idx}");
+        }
+    }
+
+    #[test]
+    fn round_trips_grids_of_several_sizes() {
+        for (width, height) in [(3, 3), (7, 4), (4, 9), (60, 40), (61, 41), (250, 3)] {
+            let grid = sample(width, height);
+            for algo in [None, Some("A*"), Some("Dead-end")] {
+                let (decoded, decoded_algo) = decode(&encode(&grid, algo)).expect("decodes");
+                assert_same(&grid, &decoded);
+                assert_eq!(decoded_algo, algo);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_without_endpoints() {
+        let grid = Grid::new(5, 6);
+        let (decoded, _) = decode(&encode(&grid, None)).unwrap();
+        assert_eq!((decoded.start(), decoded.end()), (None, None));
+        assert_same(&grid, &decoded);
+    }
+
+    #[test]
+    fn accepts_fragments_and_urls() {
+        let code = encode(&sample(9, 5), Some("BFS"));
+        for text in [format!("#{FRAGMENT_PREFIX}{code}"), format!("https://example.com/lab/#{FRAGMENT_PREFIX}{code}")] {
+            assert_eq!(decode(&text).map(|(_, algo)| algo), Some(Some("BFS")));
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let code = encode(&sample(12, 8), Some("DFS"));
+        for len in 0..code.len() {
+            assert!(decode(&code[..len]).is_none(), "prefix of length {len}");
+        }
+    }
+
+    #[test]
+    fn rejects_oversized_dimensions() {
+        let side = crate::LARGE_GRID_SIZE as u64 + 1;
+        let mut bytes = vec![VERSION];
+        push_varint(&mut bytes, side);
+        push_varint(&mut bytes, side);
+        bytes.extend([0, 0, 0]);
+        push_varint(&mut bytes, (side * side) << 2);
+        assert!(decode(&to_base64(&bytes)).is_none());
+    }
+
+    #[test]
+    fn rejects_sides_outside_the_app_range() {
+        let big = crate::LARGE_GRID_SIZE as u64 + 1;
+        for (width, height) in [(1, 1), (2, 5), (5, 2), (1_000_000, 1), (1, 1_000_000), (big, 3)] {
+            let mut bytes = vec![VERSION];
+            push_varint(&mut bytes, width);
+            push_varint(&mut bytes, height);
+            bytes.extend([0, 0, 0]);
+            push_varint(&mut bytes, (width * height) << 2);
+            assert!(decode(&to_base64(&bytes)).is_none(), "{width}x{height}");
+        }
+    }
+
+    #[test]
+    fn rejects_bad_base64() {
+        let code = encode(&sample(6, 6), None);
+        assert!(decode(&format!("{}*{}", &code[..4], &code[4..])).is_none());
+        assert!(decode("not base64!").is_none());
+    }
+
+    #[test]
+    fn rejects_unknown_version() {
+        let mut bytes = from_base64(&encode(&sample(6, 6), None)).unwrap();
+        bytes[0] = VERSION + 1;
+        assert!(decode(&to_base64(&bytes)).is_none());
+    }
+}
@@ -3,17 +3,54 @@ use macroquad::prelude::*;
 
 mod grid;
 mod stats;
+mod api;
+mod json;
+mod theme;
 mod algorithms;
 mod maze;
+mod analysis;
+mod brush;
+mod compare;
+mod console;
+mod heatmap;
+mod link;
+mod pseudocode;
+mod server;
+mod session;
 mod ui;
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+mod web;
 
 use grid::{Grid, COST_MUD, COST_WATER};
+use analysis::Report;
+use brush::{Brush, Shape, Stroke};
+use compare::Pane;
+use console::Console;
+use heatmap::Heatmap;
+use algorithms::SearchData;
+use algorithms::astar;
+use algorithms::hpa::Hierarchy;
+use maze::{Generator, Settings};
+use maze::braid::PostProcess;
+use maze::mask::Scope;
+use maze::trace::{Playback, Trace};
 use stats::Stats;
+use theme::Theme;
 use ui::Ui;
 
 const GRID_WIDTH: usize = 60;
 const GRID_HEIGHT: usize = 40;
+const LARGE_GRID_SIZE: usize = 1000;
+
+/// Grid sides the app makes and accepts from outside: 3 to `LARGE_GRID_SIZE`.
+pub fn fits(width: usize, height: usize) -> bool {
+    (3..=LARGE_GRID_SIZE).contains(&width) && (3..=LARGE_GRID_SIZE).contains(&height)
+}
+
+/// Animation speeds, in events applied per frame.
+pub const SPEEDS: [usize; 7] = [1, 2, 5, 10, 25, 100, 500];
 const SIDEBAR_WIDTH: f32 = 200.0;
+const THEME_DIR: &str = "themes";
 
 #[derive(PartialEq, Clone, Copy)]
 pub enum Tool {
@@ -21,13 +58,68 @@ pub enum Tool {
     Erase,
     Mud,
     Water,
+    Select,
+    Mask,
+}
+
+impl Tool {
+    pub const ALL: [Tool; 6] = [Tool::Wall, Tool::Erase, Tool::Mud, Tool::Water, Tool::Select, Tool::Mask];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Wall   => "Wall",
+            Tool::Erase  => "Erase",
+            Tool::Mud    => "Mud",
+            Tool::Water  => "Water",
+            Tool::Select => "Select",
+            Tool::Mask   => "Mask",
+        }
+    }
 }
 
 pub struct AppState {
     pub grid: Grid,
     pub stats: Stats,
     pub last_algo: Option<&'static str>,
+    /// Searches run so far, so observers can tell a new result from an old one.
+    pub runs: u32,
+    /// Per-cell numbers from the last search, shown by the heatmap.
+    pub search: SearchData,
+    pub heatmap: Heatmap,
+    /// Shape metrics of the last generated maze, refreshed on demand.
+    pub report: Option<Report>,
     pub active_tool: Tool,
+    /// Shape and size the painting tools draw with.
+    pub brush: Brush,
+    /// Opposite corners of the rectangle dragged out with the Select tool.
+    pub selection: Option<(usize, usize)>,
+    /// Cells painted with the Mask tool, one flag per grid cell.
+    pub mask: Vec<bool>,
+    pub hpa: Option<Hierarchy>,
+    /// Heuristic and weight for A*, set from the console.
+    pub astar: astar::Params,
+    /// Split view, one pane per algorithm. Empty for the normal single grid.
+    pub panes: Vec<Pane>,
+    /// Record graph searches too and play them back step by step.
+    pub animate_runs: bool,
+    /// Draw an arrow from each reached cell to its parent.
+    pub show_parents: bool,
+    /// Show the pseudocode panel for the last solver.
+    pub show_code: bool,
+    /// Built-in themes, then any loaded from theme files.
+    pub themes: Vec<Theme>,
+    pub theme: usize,
+    /// What loading theme files found.
+    pub theme_report: String,
+    /// Outcome of the last copy or paste of a grid link.
+    pub link_report: String,
+    pub maze_generator: Generator,
+    pub maze_playback: Option<Playback>,
+    pub maze_post: PostProcess,
+    pub maze_settings: Settings,
+    /// Limits generation to the selection, an ellipse in it, or the mask.
+    pub maze_scope: Scope,
+    pub speed: usize,
 }
 
 impl AppState {
@@ -39,20 +131,44 @@ impl AppState {
             grid,
             stats: Stats::default(),
             last_algo: None,
+            runs: 0,
+            search: SearchData::default(),
+            heatmap: Heatmap::Off,
+            report: None,
             active_tool: Tool::Wall,
+            brush: Brush::default(),
+            selection: None,
+            mask: vec![false; GRID_WIDTH * GRID_HEIGHT],
+            hpa: None,
+            astar: astar::Params::default(),
+            panes: Vec::new(),
+            animate_runs: false,
+            show_parents: false,
+            show_code: false,
+            themes: theme::builtin(),
+            theme: 0,
+            theme_report: String::new(),
+            link_report: String::new(),
+            maze_generator: Generator::RecursiveBacktracker,
+            maze_playback: None,
+            maze_post: PostProcess::default(),
+            maze_settings: Settings::default(),
+            maze_scope: Scope::All,
+            speed: 3,
         }
     }
 
+    /// Walking solvers, and the rest with `animate_runs`, record their
+    /// moves and play them back like a maze.
     pub fn run_algo(&mut self, name: &'static str) {
+        self.skip_playback();
+        self.panes.clear();
         self.grid.reset_search_state();
-        self.stats = match name {
-            "BFS"      => algorithms::bfs::run(&mut self.grid),
-            "DFS"      => algorithms::dfs::run(&mut self.grid),
-            "Dijkstra" => algorithms::dijkstra::run(&mut self.grid),
-            "A*"       => algorithms::astar::run(&mut self.grid),
-            _          => Stats::default(),
-        };
+        let mut trace = if self.animate_runs || algorithms::is_walker(name) { Trace::recording() } else { Trace::off() };
+        self.stats = algorithms::run(name, &mut self.grid, &mut trace, &mut self.search, &mut self.hpa, &self.astar);
         self.last_algo = Some(name);
+        self.runs += 1;
+        self.maze_playback = trace.into_playback(&mut self.grid);
     }
 
     pub fn reset_search(&mut self) {
@@ -61,11 +177,171 @@ impl AppState {
     }
 
     pub fn reset_all(&mut self) {
+        self.maze_playback = None;
+        self.panes.clear();
         self.grid.reset_all();
-        self.grid.set_start(self.grid.idx(1, 1));
-        self.grid.set_end(self.grid.idx(GRID_HEIGHT - 2, GRID_WIDTH - 2));
+        self.place_endpoints();
+        self.stats = Stats::default();
+        self.last_algo = None;
+        self.report = None;
+    }
+
+    /// With `animate`, the grid is rewound to the generator's starting
+    /// point and `tick` replays the carving frame by frame. Outside the
+    /// scope's mask the grid is kept as it is.
+    pub fn generate_maze(&mut self, animate: bool) {
+        let mut trace = if animate { Trace::recording() } else { Trace::off() };
+        self.panes.clear();
+        match self.maze_scope.mask(&self.grid, self.selection, &self.mask) {
+            Some(mask) => {
+                self.skip_playback();
+                self.grid.reset_search_state();
+                maze::mask::generate_masked(
+                    &mut self.grid,
+                    &mut trace,
+                    self.maze_generator,
+                    &mut self.maze_settings,
+                    &self.maze_post,
+                    &mask,
+                );
+            }
+            None => {
+                self.grid.reset_all();
+                self.maze_generator.generate(&mut self.grid, &mut trace, &mut self.maze_settings);
+                // Generators that care where the endpoints go (e.g. BSP) set them.
+                if self.grid.start().is_none() || self.grid.end().is_none() {
+                    self.place_endpoints();
+                }
+                self.maze_post.apply(&mut self.grid, &mut trace);
+            }
+        }
         self.stats = Stats::default();
         self.last_algo = None;
+        self.report = Some(analysis::analyze(&self.grid));
+        self.maze_playback = trace.into_playback(&mut self.grid);
+    }
+
+    /// Opens the split view with `count` panes, keeping the algorithms of
+    /// panes that already exist, and races them on copies of the grid.
+    /// A count of zero goes back to the single view.
+    pub fn set_panes(&mut self, count: usize) {
+        self.skip_playback();
+        self.grid.reset_search_state();
+        let algos: Vec<&'static str> = (0..count.min(compare::MAX_PANES))
+            .map(|i| self.panes.get(i).map_or(compare::DEFAULT_ALGOS[i], |pane| pane.algo))
+            .collect();
+        self.panes = algos.into_iter()
+            .map(|algo| Pane::run(algo, &self.grid, &mut self.hpa, &self.astar, self.animate_runs))
+            .collect();
+    }
+
+    pub fn set_pane_algo(&mut self, i: usize, algo: &'static str) {
+        self.panes[i] = Pane::run(algo, &self.grid, &mut self.hpa, &self.astar, self.animate_runs);
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.themes[self.theme.min(self.themes.len() - 1)]
+    }
+
+    /// Rescans the theme directory, staying on the current theme if it is
+    /// still there.
+    pub fn reload_themes(&mut self) {
+        let name = self.theme().name.clone();
+        (self.themes, self.theme_report) = theme::load(THEME_DIR);
+        self.theme = self.themes.iter().position(|t| t.name == name).unwrap_or(0);
+    }
+
+    pub fn tick(&mut self) {
+        compare::tick(&mut self.panes, SPEEDS[self.speed]);
+        let Some(playback) = &mut self.maze_playback else { return };
+        if !playback.paused {
+            playback.advance(&mut self.grid, SPEEDS[self.speed]);
+        }
+        if playback.is_done() {
+            self.skip_playback();
+        }
+    }
+
+    pub fn skip_playback(&mut self) {
+        if let Some(playback) = self.maze_playback.take() {
+            playback.finish(&mut self.grid);
+        }
+    }
+
+    /// Replaces the grid with an empty one of the given size. The HPA*
+    /// abstraction is dropped since its clusters no longer line up.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.grid = Grid::new(width, height);
+        self.hpa = None;
+        self.selection = None;
+        self.brush.stroke = None;
+        self.mask = vec![false; width * height];
+        self.reset_all();
+    }
+
+    /// Swaps in a grid from a link, file or the hosting page, then reruns
+    /// `algo` on it so the result shows straight away. Sizes the app would
+    /// not make itself are refused.
+    pub fn load_grid(&mut self, grid: Grid, algo: Option<&'static str>) -> Result<(), String> {
+        if !fits(grid.width, grid.height) {
+            return Err(format!("the grid is {}x{}; sides must be 3 to {LARGE_GRID_SIZE}", grid.width, grid.height));
+        }
+        self.resize(grid.width, grid.height);
+        self.grid = grid;
+        if let Some(algo) = algo {
+            self.run_algo(algo);
+            self.skip_playback();
+        }
+        Ok(())
+    }
+
+    /// Puts the grid and last algorithm on the clipboard as a link.
+    pub fn copy_link(&mut self) {
+        let link = link::publish(&link::encode(&self.grid, self.last_algo));
+        miniquad::window::clipboard_set(&link);
+        self.link_report = format!("Copied link ({} chars)", link.len());
+    }
+
+    /// Loads a link (or bare code) from the clipboard.
+    pub fn paste_link(&mut self) {
+        match miniquad::window::clipboard_get().as_deref().and_then(link::decode) {
+            Some((grid, algo)) => {
+                self.link_report = match self.load_grid(grid, algo) {
+                    Ok(())       => "Loaded grid from link".to_string(),
+                    Err(message) => message,
+                };
+            }
+            None => self.link_report = "Clipboard holds no grid link".to_string(),
+        }
+    }
+
+    /// Start at the top left, end at the bottom right. Perfect mazes only
+    /// carve odd rows and columns, so when the corner is a wall the end
+    /// steps back onto the last odd cell; on grids too small to hold two
+    /// cells it goes beside the start. Both are opened if still walled.
+    pub fn place_endpoints(&mut self) {
+        let grid = &mut self.grid;
+        let (height, width) = (grid.height, grid.width);
+        let odd = |n: usize| if n % 2 == 1 { n } else { n.saturating_sub(1) };
+        let start = grid.idx(1.min(height - 1), 1.min(width - 1));
+        let (last_row, last_col) = (height.saturating_sub(2), width.saturating_sub(2));
+        let corner = grid.idx(last_row, last_col);
+        let mut end = if grid.cell(corner).is_wall { grid.idx(odd(last_row), odd(last_col)) } else { corner };
+        if end == start {
+            // Beside the start, or below it in a single column; a 1x1 grid keeps both on one cell.
+            end = if start % width + 1 < width { start + 1 } else if start + width < width * height { start + width } else { start };
+        }
+        for idx in [start, end] {
+            if grid.cell(idx).is_wall { grid.set_wall(idx, false); }
+        }
+        grid.set_start(start);
+        grid.set_end(end);
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -78,95 +354,445 @@ fn window_conf() -> Conf {
     }
 }
 
-#[macroquad::main(window_conf)]
-async fn main() {
+fn main() {
+    // `--server` runs headless, before any window is opened.
+    if std::env::args().skip(1).any(|arg| arg == "--server") {
+        server::run();
+        return;
+    }
+    macroquad::Window::from_config(window_conf(), app());
+}
+
+async fn app() {
     let mut state = AppState::new();
+    state.reload_themes();
+    session::restore(&mut state);
+    // A shared link wins over the autosaved session.
+    if let Some((grid, algo)) = link::from_page().as_deref().and_then(link::decode) {
+        if let Err(message) = state.load_grid(grid, algo) { state.link_report = message; }
+    }
+    let mut autosave = session::Autosave::default();
+    #[cfg(all(feature = "web", target_arch = "wasm32"))]
+    let mut page = api::page::Page::new(&state);
     let mut ui = Ui::new();
+    let mut console = Console::default();
+    let mut rasters = Rasters::default();
 
     loop {
         let screen_w = screen_width();
         let screen_h = screen_height();
         let grid_w = screen_w - SIDEBAR_WIDTH;
-        let cell_w = grid_w / GRID_WIDTH as f32;
-        let cell_h = screen_h / GRID_HEIGHT as f32;
+        let cell_w = grid_w / state.grid.width as f32;
+        let cell_h = screen_h / state.grid.height as f32;
 
         // ── Input ──
-        handle_grid_input(&mut state, grid_w, cell_w, cell_h);
+        console.update(&mut state);
+        if !console.open {
+            handle_grid_input(&mut state, grid_w, screen_h);
+        }
+        state.tick();
+        autosave.tick(&state, get_time());
+        #[cfg(all(feature = "web", target_arch = "wasm32"))]
+        page.tick(&mut state);
 
         // ── UI ──
+        // After input, which may have resized the grid.
+        ui.hovered = hovered_cell(&state.grid, grid_w, screen_h);
         ui.draw(&mut state, grid_w, screen_w, screen_h);
 
         // ── Grid rendering ──
-        draw_grid(&state.grid, cell_w, cell_h);
+        if !state.panes.is_empty() {
+            draw_panes(&state.panes, &mut rasters, state.theme(), state.heatmap, state.show_parents, grid_w, screen_h);
+            if console.open {
+                ui.console(&console, grid_w, screen_h);
+            } else {
+                ui.inspector(&state, grid_w, screen_h);
+            }
+            next_frame().await;
+            continue;
+        }
+        let theme = state.theme();
+        draw_grid(&state.grid, &mut rasters.cells[0], theme, Vec2::ZERO, cell_w, cell_h);
+        draw_heatmap(&state.grid, &mut rasters.heat[0], theme, &state.search, state.heatmap, Vec2::ZERO, vec2(cell_w, cell_h));
+        if state.show_parents {
+            draw_parent_arrows(&state.grid, theme, &state.search, Vec2::ZERO, cell_w, cell_h);
+        }
+        if let Some(playback) = &state.maze_playback {
+            draw_playback_overlay(&state.grid, theme, playback, Vec2::ZERO, cell_w, cell_h);
+        }
+        if state.active_tool == Tool::Mask || state.maze_scope == Scope::Painted {
+            draw_mask(&state.grid, theme, &state.mask, cell_w, cell_h);
+        }
+        if let Some(stroke) = state.brush.stroke.filter(|_| matches!(state.brush.shape, Shape::Line | Shape::Rect)) {
+            draw_brush_preview(&state.grid, theme, &state.brush.span(&state.grid, stroke.anchor, stroke.last), cell_w, cell_h);
+        }
+        if let Some((a, b)) = state.selection {
+            draw_selection(&state.grid, theme, a, b, state.maze_scope == Scope::Ellipse, cell_w, cell_h);
+        }
+        if state.last_algo == Some("HPA*") {
+            if let Some(hierarchy) = &state.hpa {
+                draw_hpa_overlay(&state.grid, theme, hierarchy, cell_w, cell_h);
+            }
+        }
+        if state.show_code {
+            ui.pseudocode(&state, screen_h);
+        }
+        if console.open {
+            ui.console(&console, grid_w, screen_h);
+        } else {
+            ui.inspector(&state, grid_w, screen_h);
+        }
 
         next_frame().await;
     }
 }
 
-fn handle_grid_input(state: &mut AppState, grid_w: f32, cell_w: f32, cell_h: f32) {
-    if is_mouse_button_down(MouseButton::Left) {
-        let (mx, my) = mouse_position();
-        if mx < grid_w {
-            let col = (mx / cell_w) as usize;
-            let row = (my / cell_h) as usize;
-            if col < GRID_WIDTH && row < GRID_HEIGHT {
-                let idx = state.grid.idx(row, col);
-                match state.active_tool {
-                    Tool::Wall  => state.grid.set_wall(idx, true),
-                    Tool::Erase => state.grid.set_wall(idx, false),
-                    Tool::Mud   => state.grid.set_terrain(idx, COST_MUD),
-                    Tool::Water => state.grid.set_terrain(idx, COST_WATER),
-                }
+/// The main grid's cell under the mouse, if any.
+fn hovered_cell(grid: &Grid, grid_w: f32, screen_h: f32) -> Option<usize> {
+    let (mx, my) = mouse_position();
+    let col = (mx / (grid_w / grid.width as f32)) as usize;
+    let row = (my / (screen_h / grid.height as f32)) as usize;
+    (mx >= 0.0 && my >= 0.0 && mx < grid_w && col < grid.width && row < grid.height).then(|| grid.idx(row, col))
+}
+
+fn handle_grid_input(state: &mut AppState, grid_w: f32, screen_h: f32) {
+    // The split view only shows copies; editing waits until it is closed.
+    if !state.panes.is_empty() {
+        let paused = state.panes.iter().filter_map(|pane| pane.playback.as_ref()).any(|pb| pb.paused);
+        for pane in state.panes.iter_mut() {
+            let Some(playback) = &mut pane.playback else { continue };
+            if is_key_pressed(KeyCode::Space) { playback.paused = !paused; }
+            if is_key_pressed(KeyCode::Right) && playback.paused {
+                playback.advance_steps(&mut pane.grid, 1);
+            }
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            for pane in state.panes.iter_mut() {
+                if let Some(playback) = pane.playback.take() { playback.finish(&mut pane.grid); }
             }
         }
+        if is_key_pressed(KeyCode::Escape) { state.set_panes(0); }
+        return;
     }
 
-    if is_mouse_button_down(MouseButton::Right) {
-        let (mx, my) = mouse_position();
-        if mx < grid_w {
-            let col = (mx / cell_w) as usize;
-            let row = (my / cell_h) as usize;
-            if col < GRID_WIDTH && row < GRID_HEIGHT {
-                let idx = state.grid.idx(row, col);
-                state.grid.set_wall(idx, false);
-            }
+    // While a maze is being drawn, the grid belongs to the playback.
+    if let Some(playback) = &mut state.maze_playback {
+        if is_key_pressed(KeyCode::Space) { playback.paused = !playback.paused; }
+        if is_key_pressed(KeyCode::Right) && playback.paused {
+            playback.advance(&mut state.grid, 1);
         }
+        if is_key_pressed(KeyCode::Enter) { state.skip_playback(); }
+        if is_key_pressed(KeyCode::R) { state.reset_all(); }
+        return;
+    }
+
+    let hovered = hovered_cell(&state.grid, grid_w, screen_h);
+
+    if state.active_tool == Tool::Select && is_mouse_button_down(MouseButton::Left) {
+        if let Some(idx) = hovered {
+            let anchor = match state.selection {
+                Some((anchor, _)) if !is_mouse_button_pressed(MouseButton::Left) => anchor,
+                _ => idx,
+            };
+            state.selection = Some((anchor, idx));
+        }
+    } else {
+        paint_input(state, hovered);
     }
 
     if is_key_pressed(KeyCode::R) { state.reset_all(); }
+    if is_key_pressed(KeyCode::L) {
+        if state.grid.width == LARGE_GRID_SIZE {
+            state.resize(GRID_WIDTH, GRID_HEIGHT);
+        } else {
+            state.resize(LARGE_GRID_SIZE, LARGE_GRID_SIZE);
+        }
+    }
 }
 
-fn draw_grid(grid: &Grid, cell_w: f32, cell_h: f32) {
-    for i in 0..(grid.width * grid.height) {
-        let col = i % grid.width;
-        let row = i / grid.width;
-        let cell = grid.cell(i);
+/// Left paints with the active tool, right erases. Free strokes join the
+/// cells of successive frames so fast drags leave no gaps; Line and Rect
+/// land on release; Fill acts on the click.
+fn paint_input(state: &mut AppState, hovered: Option<usize>) {
+    let down = is_mouse_button_down(MouseButton::Left) || is_mouse_button_down(MouseButton::Right);
+    let brush = state.brush;
+    if !down {
+        if let Some(stroke) = state.brush.stroke.take() {
+            if matches!(brush.shape, Shape::Line | Shape::Rect) {
+                paint(state, &brush.span(&state.grid, stroke.anchor, stroke.last), stroke.erase);
+            }
+        }
+        return;
+    }
+    let Some(idx) = hovered else { return };
 
-        let color = if cell.is_wall         { color_u8!(69, 71, 90, 255) }
-            else if cell.is_start           { color_u8!(166, 227, 161, 255) }
-            else if cell.is_end             { color_u8!(243, 139, 168, 255) }
-            else if cell.is_path            { color_u8!(249, 226, 175, 255) }
-            else if cell.is_visited         { color_u8!(137, 180, 250, 255) }
-            else if cell.cost == grid::COST_MUD   { color_u8!(161, 138, 90, 255) }
-            else if cell.cost == grid::COST_WATER { color_u8!(90, 138, 161, 255) }
-            else                            { color_u8!(30, 30, 46, 255) };
+    let started = brush.stroke.is_none();
+    let stroke = *state.brush.stroke.get_or_insert(Stroke {
+        anchor: idx,
+        last: idx,
+        erase: !is_mouse_button_down(MouseButton::Left),
+    });
+    match brush.shape {
+        Shape::Free => paint(state, &brush.stamp(&state.grid, &brush::line(&state.grid, stroke.last, idx)), stroke.erase),
+        // Once per click, not every frame the button is held.
+        Shape::Fill if started => paint(state, &brush::flood(&state.grid, idx), stroke.erase),
+        _ => {}
+    }
+    if let Some(stroke) = &mut state.brush.stroke { stroke.last = idx; }
+}
 
-        draw_rectangle(
-            col as f32 * cell_w,
-            row as f32 * cell_h,
-            cell_w,
-            cell_h,
-            color,
-        );
+/// Applies the active tool, or erases, on `cells`.
+fn paint(state: &mut AppState, cells: &[usize], erase: bool) {
+    for &idx in cells {
+        match state.active_tool {
+            Tool::Mask   => state.mask[idx] = !erase,
+            _ if erase   => state.grid.set_wall(idx, false),
+            Tool::Wall   => state.grid.set_wall(idx, true),
+            Tool::Erase  => state.grid.set_wall(idx, false),
+            Tool::Mud    => state.grid.set_terrain(idx, COST_MUD),
+            Tool::Water  => state.grid.set_terrain(idx, COST_WATER),
+            Tool::Select => {}
+        }
     }
+}
+
+/// A cell layer rasterised for grids too fine to draw cell by cell. The
+/// texture is kept across frames and only redrawn when its key changes.
+#[derive(Default)]
+struct Raster {
+    key: Option<(u64, Theme, Heatmap)>,
+    texture: Option<Texture2D>,
+}
+
+/// Base cells and heatmap for the main grid (slot 0) and each pane after it.
+#[derive(Default)]
+struct Rasters {
+    cells: [Raster; 1 + compare::MAX_PANES],
+    heat: [Raster; 1 + compare::MAX_PANES],
+}
+
+fn draw_grid(grid: &Grid, raster: &mut Raster, theme: &Theme, origin: Vec2, cell_w: f32, cell_h: f32) {
+    draw_cells(grid, raster, (theme, Heatmap::Off), origin, vec2(cell_w, cell_h), |i| cell_color(grid, theme, i));
 
     // Grid lines
     if cell_w > 6.0 {
+        let (full_w, full_h) = (grid.width as f32 * cell_w, grid.height as f32 * cell_h);
         for r in 0..=grid.height {
-            draw_line(0.0, r as f32 * cell_h, grid.width as f32 * cell_w, r as f32 * cell_h, 0.5, color_u8!(0, 0, 0, 20));
+            let y = origin.y + r as f32 * cell_h;
+            draw_line(origin.x, y, origin.x + full_w, y, 0.5, theme.grid_line);
         }
         for c in 0..=grid.width {
-            draw_line(c as f32 * cell_w, 0.0, c as f32 * cell_w, grid.height as f32 * cell_h, 0.5, color_u8!(0, 0, 0, 20));
+            let x = origin.x + c as f32 * cell_w;
+            draw_line(x, origin.y, x, origin.y + full_h, 0.5, theme.grid_line);
+        }
+    }
+}
+
+/// Paints every cell with `color`, skipping fully transparent ones.
+/// `style` is whatever besides the grid the colours depend on.
+fn draw_cells(grid: &Grid, raster: &mut Raster, style: (&Theme, Heatmap), origin: Vec2, cell: Vec2, color: impl Fn(usize) -> Color) {
+    let (cell_w, cell_h) = (cell.x, cell.y);
+    // Past a couple of pixels per cell, one rectangle per cell is too slow;
+    // rasterise into a texture instead and let the GPU scale it. The texture
+    // is reused until the grid or the style changes.
+    if cell_w < 2.0 || cell_h < 2.0 {
+        let version = grid.version();
+        let fresh = raster.key.as_ref().is_some_and(|(v, theme, heatmap)| (*v, theme, *heatmap) == (version, style.0, style.1));
+        if !fresh || raster.texture.is_none() {
+            let mut image = Image::gen_image_color(grid.width as u16, grid.height as u16, BLANK);
+            for i in 0..(grid.width * grid.height) {
+                image.set_pixel((i % grid.width) as u32, (i / grid.width) as u32, color(i));
+            }
+            match &raster.texture {
+                Some(texture) if texture.width() as usize == grid.width && texture.height() as usize == grid.height => {
+                    texture.update(&image);
+                }
+                _ => {
+                    let texture = Texture2D::from_image(&image);
+                    texture.set_filter(FilterMode::Nearest);
+                    raster.texture = Some(texture);
+                }
+            }
+            raster.key = Some((version, style.0.clone(), style.1));
+        }
+        let Some(texture) = &raster.texture else { return };
+        draw_texture_ex(texture, origin.x, origin.y, WHITE, DrawTextureParams {
+            dest_size: Some(vec2(grid.width as f32 * cell_w, grid.height as f32 * cell_h)),
+            ..Default::default()
+        });
+        return;
+    }
+
+    for i in 0..(grid.width * grid.height) {
+        let c = color(i);
+        if c.a == 0.0 { continue; }
+        draw_rectangle(
+            origin.x + (i % grid.width) as f32 * cell_w,
+            origin.y + (i / grid.width) as f32 * cell_h,
+            cell_w,
+            cell_h,
+            c,
+        );
+    }
+}
+
+/// Colours expanded cells by the chosen search value. Path cells and the
+/// endpoints keep their own colours so the route stays readable on top.
+fn draw_heatmap(grid: &Grid, raster: &mut Raster, theme: &Theme, data: &SearchData, heatmap: Heatmap, origin: Vec2, cell: Vec2) {
+    let Some((low, high)) = heatmap.range(data) else { return };
+    let span = (high - low).max(1) as f32;
+    draw_cells(grid, raster, (theme, heatmap), origin, cell, |i| {
+        let cell = grid.cell(i);
+        match heatmap.value(data, i) {
+            Some(v) if cell.is_visited && !cell.is_path && !cell.is_start => heatmap::ramp(&theme.ramp, (v - low) as f32 / span),
+            _ => BLANK,
         }
+    });
+}
+
+/// Arrow from every reached cell toward the cell it was reached from.
+fn draw_parent_arrows(grid: &Grid, theme: &Theme, data: &SearchData, origin: Vec2, cell_w: f32, cell_h: f32) {
+    if cell_w.min(cell_h) < 6.0 { return; }
+    let color = theme.arrow;
+    let center = |i: usize| origin + vec2(((i % grid.width) as f32 + 0.5) * cell_w, ((i / grid.width) as f32 + 0.5) * cell_h);
+    for (i, &parent) in data.parent.iter().enumerate() {
+        let cell = grid.cell(i);
+        if parent == usize::MAX || !(cell.is_open || cell.is_visited || cell.is_path) { continue; }
+        let (from, to) = (center(i), center(parent));
+        let dir = to - from;
+        let tip = from + dir * 0.45;
+        let tail = from - dir * 0.25;
+        let back = -dir.normalize() * cell_w.min(cell_h) * 0.25;
+        draw_line(tail.x, tail.y, tip.x, tip.y, 1.5, color);
+        for side in [-1.0, 1.0] {
+            let barb = tip + back + back.perp() * side * 0.7;
+            draw_line(tip.x, tip.y, barb.x, barb.y, 1.5, color);
+        }
+    }
+}
+
+fn cell_color(grid: &Grid, theme: &Theme, idx: usize) -> Color {
+    let cell = grid.cell(idx);
+
+    if cell.is_wall                       { theme.wall }
+    else if cell.is_start                 { theme.start }
+    else if cell.is_end                   { theme.end }
+    else if cell.is_path                  { theme.path }
+    else if cell.is_visited               { theme.visited }
+    else if cell.is_open                  { theme.open }
+    else if cell.cost == grid::COST_MUD   { theme.mud }
+    else if cell.cost == grid::COST_WATER { theme.water }
+    else                                  { theme.floor }
+}
+
+/// Where a Line or Rect drag will paint when the button is let go.
+fn draw_brush_preview(grid: &Grid, theme: &Theme, cells: &[usize], cell_w: f32, cell_h: f32) {
+    let color = Color { a: 0.5, ..theme.accent };
+    for &idx in cells {
+        let (row, col) = (idx / grid.width, idx % grid.width);
+        draw_rectangle(col as f32 * cell_w, row as f32 * cell_h, cell_w, cell_h, color);
     }
-}
\ No newline at end of file
+}
+
+fn draw_selection(grid: &Grid, theme: &Theme, a: usize, b: usize, ellipse: bool, cell_w: f32, cell_h: f32) {
+    let (r0, r1) = ((a / grid.width).min(b / grid.width), (a / grid.width).max(b / grid.width));
+    let (c0, c1) = ((a % grid.width).min(b % grid.width), (a % grid.width).max(b % grid.width));
+    let (x, y) = (c0 as f32 * cell_w, r0 as f32 * cell_h);
+    let (w, h) = ((c1 - c0 + 1) as f32 * cell_w, (r1 - r0 + 1) as f32 * cell_h);
+    draw_rectangle_lines(x, y, w, h, 2.0, theme.yellow);
+    if ellipse {
+        draw_ellipse_lines(x + w / 2.0, y + h / 2.0, w / 2.0, h / 2.0, 0.0, 2.0, theme.yellow);
+    }
+}
+
+fn draw_mask(grid: &Grid, theme: &Theme, mask: &[bool], cell_w: f32, cell_h: f32) {
+    for (i, &inside) in mask.iter().enumerate() {
+        if inside {
+            let x = (i % grid.width) as f32 * cell_w;
+            let y = (i / grid.width) as f32 * cell_h;
+            draw_rectangle(x, y, cell_w, cell_h, Color { a: 0.35, ..theme.mauve });
+        }
+    }
+}
+
+fn draw_playback_overlay(grid: &Grid, theme: &Theme, playback: &Playback, origin: Vec2, cell_w: f32, cell_h: f32) {
+    for (i, &marked) in playback.marked.iter().enumerate() {
+        if marked {
+            let x = origin.x + (i % grid.width) as f32 * cell_w;
+            let y = origin.y + (i / grid.width) as f32 * cell_h;
+            draw_rectangle(x, y, cell_w, cell_h, theme.trail);
+        }
+    }
+    if let Some(head) = playback.head {
+        let x = origin.x + (head % grid.width) as f32 * cell_w;
+        let y = origin.y + (head / grid.width) as f32 * cell_h;
+        draw_rectangle(x, y, cell_w, cell_h, theme.current);
+    }
+}
+
+fn draw_panes(panes: &[Pane], rasters: &mut Rasters, theme: &Theme, heatmap: Heatmap, arrows: bool, area_w: f32, area_h: f32) {
+    for (i, pane) in panes.iter().enumerate() {
+        let (x, y, w, h) = compare::layout(i, panes.len(), area_w, area_h);
+        let origin = vec2(x, y);
+        let cell_w = w / pane.grid.width as f32;
+        let cell_h = h / pane.grid.height as f32;
+        draw_grid(&pane.grid, &mut rasters.cells[i + 1], theme, origin, cell_w, cell_h);
+        draw_heatmap(&pane.grid, &mut rasters.heat[i + 1], theme, &pane.search, heatmap, origin, vec2(cell_w, cell_h));
+        if arrows {
+            draw_parent_arrows(&pane.grid, theme, &pane.search, origin, cell_w, cell_h);
+        }
+        if let Some(playback) = &pane.playback {
+            draw_playback_overlay(&pane.grid, theme, playback, origin, cell_w, cell_h);
+        }
+
+        let label = match pane.playback {
+            Some(_) => pane.algo.to_string(),
+            None if pane.stats.path_found => format!(
+                "{}  {} nodes  path {}  cost {}",
+                pane.algo, pane.stats.nodes_explored, pane.stats.path_length, pane.stats.path_cost,
+            ),
+            None    => format!("{}  {} nodes  no path", pane.algo, pane.stats.nodes_explored),
+        };
+        let text_w = measure_text(&label, None, 16, 1.0).width;
+        draw_rectangle(x, y, text_w + 12.0, 22.0, Color { a: 0.86, ..theme.bg });
+        draw_text(&label, x + 6.0, y + 16.0, 16.0, theme.text);
+        draw_rectangle_lines(x, y, w, h, 2.0, theme.bg);
+    }
+}
+
+fn draw_hpa_overlay(grid: &Grid, theme: &Theme, hierarchy: &Hierarchy, cell_w: f32, cell_h: f32) {
+    let size = hierarchy.cluster_size as f32;
+    let full_w = grid.width as f32 * cell_w;
+    let full_h = grid.height as f32 * cell_h;
+
+    // Cluster boundaries
+    for r in 1..hierarchy.rows {
+        let y = r as f32 * size * cell_h;
+        draw_line(0.0, y, full_w, y, 1.0, Color { a: 0.63, ..theme.mauve });
+    }
+    for c in 1..hierarchy.cols {
+        let x = c as f32 * size * cell_w;
+        draw_line(x, 0.0, x, full_h, 1.0, Color { a: 0.63, ..theme.mauve });
+    }
+
+    // Entrance nodes
+    let dot_w = (cell_w * 0.5).max(1.0);
+    let dot_h = (cell_h * 0.5).max(1.0);
+    for &idx in hierarchy.entrances() {
+        let x = (idx % grid.width) as f32 * cell_w + (cell_w - dot_w) / 2.0;
+        let y = (idx / grid.width) as f32 * cell_h + (cell_h - dot_h) / 2.0;
+        draw_rectangle(x, y, dot_w, dot_h, theme.mauve);
+    }
+
+    // Abstract path
+    let center = |idx: usize| {
+        vec2(
+            (idx % grid.width) as f32 * cell_w + cell_w / 2.0,
+            (idx / grid.width) as f32 * cell_h + cell_h / 2.0,
+        )
+    };
+    for pair in hierarchy.abstract_path.windows(2) {
+        let (a, b) = (center(pair[0]), center(pair[1]));
+        draw_line(a.x, a.y, b.x, b.y, 2.0, theme.peach);
+    }
+}
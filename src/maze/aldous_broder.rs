@@ -0,0 +1,31 @@
+// src/maze/aldous_broder.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, maze_cells, maze_neighbors, random_index};
+use super::trace::{Event, Trace};
+
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
+
+    let cells = maze_cells(grid);
+    if cells.is_empty() { return; }
+
+    let mut visited = vec![false; grid.width * grid.height];
+    let mut current = cells[random_index(cells.len())];
+    let mut remaining = cells.len() - 1;
+    visited[current] = true;
+    carve(grid, trace, current);
+
+    // Plain random walk; carve only on first entry into a cell.
+    while remaining > 0 {
+        let options = maze_neighbors(grid, current);
+        let (next, wall) = options[random_index(options.len())];
+        trace.push(Event::Head(next));
+        if !visited[next] {
+            visited[next] = true;
+            carve(grid, trace, wall);
+            carve(grid, trace, next);
+            remaining -= 1;
+        }
+        current = next;
+    }
+}
@@ -0,0 +1,24 @@
+// src/maze/binary_tree.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, maze_cells, random_index};
+use super::trace::{Event, Trace};
+
+/// Every cell links north or west. Leaves a diagonal bias and two open
+/// corridors along the top row and left column.
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
+
+    for cell in maze_cells(grid) {
+        trace.push(Event::Head(cell));
+        carve(grid, trace, cell);
+        let row = cell / grid.width;
+        let col = cell % grid.width;
+
+        let mut options = Vec::with_capacity(2);
+        if row >= 3 { options.push(grid.idx(row - 1, col)); }
+        if col >= 3 { options.push(grid.idx(row, col - 1)); }
+        if !options.is_empty() {
+            carve(grid, trace, options[random_index(options.len())]);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// src/maze/braid.rs
+use crate::grid::{Grid, COST_MUD, COST_WATER};
+use super::{carve, maze_cells, maze_neighbors, random_index, shuffle};
+use super::trace::{Event, Trace};
+
+/// Fractions offered by the sidebar for each post-process setting.
+pub const LEVELS: [f32; 5] = [0.0, 0.1, 0.25, 0.5, 1.0];
+
+/// Post-processing applied after a generator to turn a perfect maze into one
+/// with loops, so that BFS, Dijkstra and A* stop agreeing on the only route.
+#[derive(Clone, Copy, Default)]
+pub struct PostProcess {
+    /// Fraction of dead ends to open up into a neighbouring corridor.
+    pub dead_ends: f32,
+    /// Fraction of the remaining inner walls to knock down.
+    pub walls: f32,
+    /// Fraction of corridor cells turned into mud or water.
+    pub terrain: f32,
+}
+
+impl PostProcess {
+    pub fn apply(&self, grid: &mut Grid, trace: &mut Trace) {
+        if self.dead_ends > 0.0 { braid(grid, trace, self.dead_ends); }
+        if self.walls > 0.0     { remove_walls(grid, trace, self.walls); }
+        if self.terrain > 0.0   { sprinkle_terrain(grid, trace, self.terrain); }
+    }
+}
+
+/// Removes dead ends by carving one of their closed walls, preferring a wall
+/// that also fixes a neighbouring dead end.
+pub fn braid(grid: &mut Grid, trace: &mut Trace, fraction: f32) {
+    let mut dead_ends = maze_cells(grid)
+        .into_iter()
+        .filter(|&idx| is_dead_end(grid, idx))
+        .collect::<Vec<_>>();
+    shuffle(&mut dead_ends);
+
+    let count = (dead_ends.len() as f32 * fraction).round() as usize;
+    for &cell in dead_ends.iter().take(count) {
+        // An earlier removal may already have opened this one.
+        if !is_dead_end(grid, cell) { continue; }
+
+        let closed = maze_neighbors(grid, cell)
+            .into_iter()
+            .filter(|&(neighbor, wall)| grid.cell(wall).is_wall && !grid.cell(neighbor).is_wall)
+            .collect::<Vec<_>>();
+        if closed.is_empty() { continue; }
+
+        let (_, wall) = closed.iter()
+            .copied()
+            .find(|&(neighbor, _)| is_dead_end(grid, neighbor))
+            .unwrap_or(closed[random_index(closed.len())]);
+        trace.push(Event::Head(cell));
+        carve(grid, trace, wall);
+    }
+}
+
+/// Knocks down a random fraction of the walls that separate two open cells.
+pub fn remove_walls(grid: &mut Grid, trace: &mut Trace, fraction: f32) {
+    let mut walls = Vec::new();
+    for cell in maze_cells(grid) {
+        if grid.cell(cell).is_wall { continue; }
+        let row = cell / grid.width;
+        let col = cell % grid.width;
+        if col + 2 < grid.width && !grid.cell(cell + 2).is_wall {
+            walls.push(grid.idx(row, col + 1));
+        }
+        if row + 2 < grid.height && !grid.cell(cell + 2 * grid.width).is_wall {
+            walls.push(grid.idx(row + 1, col));
+        }
+    }
+    walls.retain(|&idx| grid.cell(idx).is_wall);
+    shuffle(&mut walls);
+
+    let count = (walls.len() as f32 * fraction).round() as usize;
+    for &wall in walls.iter().take(count) {
+        carve(grid, trace, wall);
+    }
+}
+
+/// Turns a random fraction of corridor cells into mud or water.
+pub fn sprinkle_terrain(grid: &mut Grid, trace: &mut Trace, fraction: f32) {
+    let mut corridors = (0..grid.width * grid.height)
+        .filter(|&i| {
+            let cell = grid.cell(i);
+            !cell.is_wall && !cell.is_start && !cell.is_end
+        })
+        .collect::<Vec<_>>();
+    shuffle(&mut corridors);
+
+    let count = (corridors.len() as f32 * fraction).round() as usize;
+    for &idx in corridors.iter().take(count) {
+        let cost = if random_index(2) == 0 { COST_MUD } else { COST_WATER };
+        grid.set_terrain(idx, cost);
+        trace.push(Event::Terrain(idx, cost));
+    }
+}
+
+fn is_dead_end(grid: &Grid, idx: usize) -> bool {
+    !grid.cell(idx).is_wall && grid.neighbors(idx).len() == 1
+}
@@ -0,0 +1,169 @@
+// src/maze/bsp.rs
+use std::collections::VecDeque;
+use crate::grid::Grid;
+use super::{carve, fill_walls, random_index};
+use super::trace::{Event, Trace};
+
+#[derive(Clone, Copy)]
+pub struct Params {
+    /// Smallest partition side, walls included. Rooms are always smaller.
+    pub min_leaf: usize,
+    /// Put start and end in the two rooms farthest apart by walking distance.
+    pub far_endpoints: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { min_leaf: 8, far_endpoints: true }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Rect {
+    row: usize,
+    col: usize,
+    height: usize,
+    width: usize,
+}
+
+impl Rect {
+    fn center(&self) -> (usize, usize) {
+        (self.row + self.height / 2, self.col + self.width / 2)
+    }
+}
+
+/// Binary space partition: split the map into leaves, put a room in each
+/// leaf, and join sibling subtrees with L-shaped corridors on the way back up.
+/// Start and end are placed in rooms rather than the usual corners.
+pub fn generate(grid: &mut Grid, trace: &mut Trace, params: &Params) {
+    fill_walls(grid, trace);
+    if grid.width < 5 || grid.height < 5 { return; }
+
+    let min_leaf = params.min_leaf.max(5);
+    let bounds = Rect { row: 1, col: 1, height: grid.height - 2, width: grid.width - 2 };
+    let mut rooms = Vec::new();
+    split(grid, trace, bounds, min_leaf, &mut rooms);
+    let centers: Vec<usize> = rooms.iter()
+        .map(|room| { let (row, col) = room.center(); grid.idx(row, col) })
+        .collect();
+    let (Some(&first), Some(&last)) = (centers.first(), centers.last()) else { return };
+
+    let (start, end) = match rooms.as_slice() {
+        // A lone room gets its opposite corners; a one-cell room is widened
+        // into its leaf's wall so the two never share a cell.
+        [room] => {
+            let near = grid.idx(room.row, room.col);
+            let mut far = grid.idx(room.row + room.height - 1, room.col + room.width - 1);
+            if far == near {
+                far = near + 1;
+                carve(grid, trace, far);
+            }
+            (near, far)
+        }
+        // Double sweep: the room farthest from any room, then the room
+        // farthest from that one.
+        _ if params.far_endpoints => {
+            let a = farthest(grid, first, &centers);
+            (a, farthest(grid, a, &centers))
+        }
+        _ => (first, last),
+    };
+    grid.set_start(start);
+    grid.set_end(end);
+}
+
+/// Returns the index of one room carved inside `rect`, connecting the two
+/// halves if it was split.
+fn split(grid: &mut Grid, trace: &mut Trace, rect: Rect, min_leaf: usize, rooms: &mut Vec<Rect>) -> usize {
+    let can_split_h = rect.height >= 2 * min_leaf;
+    let can_split_v = rect.width >= 2 * min_leaf;
+
+    if !can_split_h && !can_split_v {
+        let room = place_room(rect);
+        for row in room.row..room.row + room.height {
+            for col in room.col..room.col + room.width {
+                carve(grid, trace, grid.idx(row, col));
+            }
+        }
+        rooms.push(room);
+        return rooms.len() - 1;
+    }
+
+    let horizontal = if can_split_h && can_split_v {
+        if rect.height * 4 > rect.width * 5 { true }
+        else if rect.width * 4 > rect.height * 5 { false }
+        else { random_index(2) == 0 }
+    } else {
+        can_split_h
+    };
+
+    let (first, second) = if horizontal {
+        let cut = min_leaf + random_index(rect.height - 2 * min_leaf + 1);
+        (
+            Rect { height: cut, ..rect },
+            Rect { row: rect.row + cut, height: rect.height - cut, ..rect },
+        )
+    } else {
+        let cut = min_leaf + random_index(rect.width - 2 * min_leaf + 1);
+        (
+            Rect { width: cut, ..rect },
+            Rect { col: rect.col + cut, width: rect.width - cut, ..rect },
+        )
+    };
+
+    let a = split(grid, trace, first, min_leaf, rooms);
+    let b = split(grid, trace, second, min_leaf, rooms);
+    corridor(grid, trace, rooms[a].center(), rooms[b].center());
+    if random_index(2) == 0 { a } else { b }
+}
+
+/// Random room inside a leaf, leaving at least one wall cell on every side.
+fn place_room(leaf: Rect) -> Rect {
+    let max_h = leaf.height.saturating_sub(2).max(1);
+    let max_w = leaf.width.saturating_sub(2).max(1);
+    let height = (max_h / 2).max(1) + random_index(max_h - (max_h / 2).max(1) + 1);
+    let width = (max_w / 2).max(1) + random_index(max_w - (max_w / 2).max(1) + 1);
+    Rect {
+        row: leaf.row + 1 + random_index(max_h - height + 1),
+        col: leaf.col + 1 + random_index(max_w - width + 1),
+        height,
+        width,
+    }
+}
+
+fn corridor(grid: &mut Grid, trace: &mut Trace, from: (usize, usize), to: (usize, usize)) {
+    let (r0, c0) = from;
+    let (r1, c1) = to;
+    // Bend either horizontally-first or vertically-first.
+    let corner = if random_index(2) == 0 { (r0, c1) } else { (r1, c0) };
+    trace.push(Event::Head(grid.idx(corner.0, corner.1)));
+
+    for (a, b) in [(from, corner), (corner, to)] {
+        for row in a.0.min(b.0)..=a.0.max(b.0) {
+            for col in a.1.min(b.1)..=a.1.max(b.1) {
+                let idx = grid.idx(row, col);
+                if grid.cell(idx).is_wall { carve(grid, trace, idx); }
+            }
+        }
+    }
+}
+
+fn farthest(grid: &Grid, from: usize, targets: &[usize]) -> usize {
+    let mut dist = vec![usize::MAX; grid.width * grid.height];
+    let mut queue = VecDeque::new();
+    dist[from] = 0;
+    queue.push_back(from);
+    while let Some(current) = queue.pop_front() {
+        for neighbor in grid.neighbors(current) {
+            if dist[neighbor] == usize::MAX {
+                dist[neighbor] = dist[current] + 1;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+    targets.iter()
+        .copied()
+        .filter(|&t| dist[t] != usize::MAX)
+        .max_by_key(|&t| dist[t])
+        .unwrap_or(from)
+}
@@ -0,0 +1,78 @@
+// src/maze/cave.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, open_corners, place_wall, random_index};
+use super::trace::{Event, Trace};
+
+#[derive(Clone, Copy)]
+pub struct Params {
+    /// Chance that an interior cell starts out as wall.
+    pub fill: f32,
+    /// An open cell becomes wall with at least this many wall neighbours.
+    pub birth: usize,
+    /// A wall cell stays wall with at least this many wall neighbours.
+    pub survival: usize,
+    pub iterations: usize,
+    /// Tunnel isolated pockets into the main cave instead of filling them.
+    pub connect_pockets: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { fill: 0.45, birth: 5, survival: 4, iterations: 5, connect_pockets: true }
+    }
+}
+
+pub fn generate(grid: &mut Grid, trace: &mut Trace, params: &Params) {
+    fill_walls(grid, trace);
+    if grid.width < 3 || grid.height < 3 { return; }
+
+    // Random fill; the border always stays wall.
+    for row in 1..grid.height - 1 {
+        for col in 1..grid.width - 1 {
+            if (random_index(1000) as f32) >= params.fill * 1000.0 {
+                carve(grid, trace, grid.idx(row, col));
+            }
+        }
+    }
+    trace.begin(grid);
+
+    for _ in 0..params.iterations {
+        let walls: Vec<bool> = (0..grid.width * grid.height)
+            .map(|i| {
+                let (row, col) = (i / grid.width, i % grid.width);
+                if row == 0 || col == 0 || row == grid.height - 1 || col == grid.width - 1 {
+                    return true;
+                }
+                let count = wall_neighbors(grid, row, col);
+                if grid.cell(i).is_wall { count >= params.survival } else { count >= params.birth }
+            })
+            .collect();
+
+        for (i, &wall) in walls.iter().enumerate() {
+            if wall != grid.cell(i).is_wall {
+                trace.push(Event::Head(i));
+                if wall { place_wall(grid, trace, i) } else { carve(grid, trace, i) }
+            }
+        }
+    }
+
+    open_corners(grid, trace, params.connect_pockets);
+}
+
+/// Wall count in the 8-neighbourhood, treating out-of-bounds as wall.
+fn wall_neighbors(grid: &Grid, row: usize, col: usize) -> usize {
+    let mut count = 0;
+    for dr in -1i32..=1 {
+        for dc in -1i32..=1 {
+            if dr == 0 && dc == 0 { continue; }
+            let r = row as i32 + dr;
+            let c = col as i32 + dc;
+            if r < 0 || c < 0 || r >= grid.height as i32 || c >= grid.width as i32
+                || grid.cell(grid.idx(r as usize, c as usize)).is_wall
+            {
+                count += 1;
+            }
+        }
+    }
+    count
+}
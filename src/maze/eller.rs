@@ -0,0 +1,60 @@
+// src/maze/eller.rs
+use std::collections::HashSet;
+use crate::grid::Grid;
+use super::{carve, fill_walls, random_index, shuffle};
+use super::trace::{Event, Trace};
+
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
+
+    let cols: Vec<usize> = (1..grid.width).step_by(2).collect();
+    let rows: Vec<usize> = (1..grid.height).step_by(2).collect();
+    if cols.is_empty() || rows.is_empty() { return; }
+
+    // Only the current row's set ids are ever needed, so they live in one Vec.
+    let mut set = vec![0usize; cols.len()];
+    let mut next_set = 0;
+    let mut fresh = vec![true; cols.len()];
+
+    for (ri, &row) in rows.iter().enumerate() {
+        let last_row = ri + 1 == rows.len();
+
+        for (ci, &col) in cols.iter().enumerate() {
+            if fresh[ci] {
+                set[ci] = next_set;
+                next_set += 1;
+            }
+            carve(grid, trace, grid.idx(row, col));
+        }
+
+        // Randomly join horizontally adjacent cells from different sets.
+        // The last row must join everything that is still separate.
+        for ci in 0..cols.len().saturating_sub(1) {
+            trace.push(Event::Head(grid.idx(row, cols[ci])));
+            if set[ci] != set[ci + 1] && (last_row || random_index(2) == 0) {
+                carve(grid, trace, grid.idx(row, cols[ci] + 1));
+                let (keep, gone) = (set[ci], set[ci + 1]);
+                for s in set.iter_mut() {
+                    if *s == gone { *s = keep; }
+                }
+            }
+        }
+
+        if last_row { break; }
+
+        // Each set drops at least one passage into the next row.
+        let next_row = rows[ri + 1];
+        fresh = vec![true; cols.len()];
+        let mut members: Vec<usize> = (0..cols.len()).collect();
+        shuffle(&mut members);
+        let mut has_drop = HashSet::new();
+        for &ci in &members {
+            trace.push(Event::Head(grid.idx(row, cols[ci])));
+            if has_drop.insert(set[ci]) || random_index(3) == 0 {
+                carve(grid, trace, grid.idx(row + 1, cols[ci]));
+                carve(grid, trace, grid.idx(next_row, cols[ci]));
+                fresh[ci] = false;
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// src/maze/growing_tree.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, maze_neighbors, random_index};
+use super::trace::{Event, Trace};
+
+/// Which active cell to grow from next. `Newest` behaves like the recursive
+/// backtracker, `Random` like Prim's, `Oldest` gives long straight corridors.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Policy {
+    Newest,
+    Random,
+    Oldest,
+}
+
+pub fn generate(grid: &mut Grid, trace: &mut Trace, policy: Policy) {
+    fill_walls(grid, trace);
+
+    let mut visited = vec![false; grid.width * grid.height];
+    let mut active = Vec::new();
+
+    let start = grid.idx(1, 1);
+    visited[start] = true;
+    carve(grid, trace, start);
+    active.push(start);
+    trace.push(Event::Mark(start));
+
+    while !active.is_empty() {
+        let pick = match policy {
+            Policy::Newest => active.len() - 1,
+            Policy::Random => random_index(active.len()),
+            Policy::Oldest => 0,
+        };
+        let current = active[pick];
+        trace.push(Event::Head(current));
+
+        let unvisited = maze_neighbors(grid, current)
+            .into_iter()
+            .filter(|&(neighbor, _)| !visited[neighbor])
+            .collect::<Vec<_>>();
+
+        if unvisited.is_empty() {
+            active.remove(pick);
+            trace.push(Event::Unmark(current));
+        } else {
+            let (neighbor, wall) = unvisited[random_index(unvisited.len())];
+            carve(grid, trace, wall);
+            carve(grid, trace, neighbor);
+            visited[neighbor] = true;
+            active.push(neighbor);
+            trace.push(Event::Mark(neighbor));
+        }
+    }
+}
@@ -0,0 +1,60 @@
+// src/maze/hunt_and_kill.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, maze_cells, maze_neighbors, random_index};
+use super::trace::{Event, Trace};
+
+/// Random walk until stuck, then scan for the first unvisited cell that
+/// borders the maze and resume from there. No stack is kept.
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
+
+    let cells = maze_cells(grid);
+    if cells.is_empty() { return; }
+
+    let mut visited = vec![false; grid.width * grid.height];
+    let mut current = Some(cells[random_index(cells.len())]);
+    let mut scan_from = 0;
+
+    if let Some(start) = current {
+        visited[start] = true;
+        carve(grid, trace, start);
+    }
+
+    while let Some(cell) = current {
+        trace.push(Event::Head(cell));
+        // Kill: walk to a random unvisited neighbour.
+        let unvisited = maze_neighbors(grid, cell)
+            .into_iter()
+            .filter(|&(neighbor, _)| !visited[neighbor])
+            .collect::<Vec<_>>();
+
+        if !unvisited.is_empty() {
+            let (neighbor, wall) = unvisited[random_index(unvisited.len())];
+            carve(grid, trace, wall);
+            carve(grid, trace, neighbor);
+            visited[neighbor] = true;
+            current = Some(neighbor);
+            continue;
+        }
+
+        // Hunt: everything before `scan_from` is already visited.
+        current = None;
+        while scan_from < cells.len() && visited[cells[scan_from]] { scan_from += 1; }
+        for &candidate in &cells[scan_from..] {
+            if visited[candidate] { continue; }
+            trace.push(Event::Head(candidate));
+            let linked = maze_neighbors(grid, candidate)
+                .into_iter()
+                .filter(|&(neighbor, _)| visited[neighbor])
+                .collect::<Vec<_>>();
+            if !linked.is_empty() {
+                let (_, wall) = linked[random_index(linked.len())];
+                carve(grid, trace, wall);
+                carve(grid, trace, candidate);
+                visited[candidate] = true;
+                current = Some(candidate);
+                break;
+            }
+        }
+    }
+}
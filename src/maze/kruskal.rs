@@ -0,0 +1,61 @@
+// src/maze/kruskal.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, maze_cells, shuffle};
+use super::trace::{Event, Trace};
+
+struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    fn new(size: usize) -> Self {
+        Self { parent: (0..size).collect(), rank: vec![0; size] }
+    }
+
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    fn union(&mut self, a: usize, b: usize) -> bool {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb { return false; }
+        match self.rank[ra].cmp(&self.rank[rb]) {
+            std::cmp::Ordering::Less    => self.parent[ra] = rb,
+            std::cmp::Ordering::Greater => self.parent[rb] = ra,
+            std::cmp::Ordering::Equal   => {
+                self.parent[rb] = ra;
+                self.rank[ra] += 1;
+            }
+        }
+        true
+    }
+}
+
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
+
+    // Every wall between two maze cells is a candidate edge.
+    let mut edges = Vec::new();
+    for cell in maze_cells(grid) {
+        carve(grid, trace, cell);
+        let row = cell / grid.width;
+        let col = cell % grid.width;
+        if col + 2 < grid.width  { edges.push((cell, grid.idx(row, col + 1), grid.idx(row, col + 2))); }
+        if row + 2 < grid.height { edges.push((cell, grid.idx(row + 1, col), grid.idx(row + 2, col))); }
+    }
+    shuffle(&mut edges);
+    trace.begin(grid);
+
+    let mut sets = UnionFind::new(grid.width * grid.height);
+    for (a, wall, b) in edges {
+        trace.push(Event::Head(wall));
+        if sets.union(a, b) {
+            carve(grid, trace, wall);
+        }
+    }
+}
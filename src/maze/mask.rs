@@ -0,0 +1,148 @@
+// src/maze/mask.rs
+use crate::grid::Grid;
+use super::{carve, fix_pockets, random_index, Generator, Settings};
+use super::braid::PostProcess;
+use super::trace::Trace;
+
+/// Which part of the grid a generator is allowed to rewrite.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Scope {
+    All,
+    Selection,
+    /// The ellipse inscribed in the selection rectangle.
+    Ellipse,
+    /// Cells painted with the Mask tool.
+    Painted,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [Scope::All, Scope::Selection, Scope::Ellipse, Scope::Painted];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scope::All       => "All",
+            Scope::Selection => "Rect",
+            Scope::Ellipse   => "Ellipse",
+            Scope::Painted   => "Painted",
+        }
+    }
+
+    /// Per-cell mask for this scope, or `None` for the whole grid. A scope
+    /// with nothing selected or painted yields an empty mask.
+    pub fn mask(self, grid: &Grid, selection: Option<(usize, usize)>, painted: &[bool]) -> Option<Vec<bool>> {
+        let size = grid.width * grid.height;
+        match self {
+            Scope::All     => None,
+            Scope::Painted => Some(painted.to_vec()),
+            Scope::Selection | Scope::Ellipse => {
+                let mut mask = vec![false; size];
+                let Some((a, b)) = selection else { return Some(mask) };
+                let (r0, r1) = ((a / grid.width).min(b / grid.width), (a / grid.width).max(b / grid.width));
+                let (c0, c1) = ((a % grid.width).min(b % grid.width), (a % grid.width).max(b % grid.width));
+                // Centre and radii in cell units, measured to the outer edges.
+                let (cy, cx) = ((r0 + r1) as f32 / 2.0, (c0 + c1) as f32 / 2.0);
+                let (ry, rx) = ((r1 - r0 + 1) as f32 / 2.0, (c1 - c0 + 1) as f32 / 2.0);
+                for row in r0..=r1 {
+                    for col in c0..=c1 {
+                        let (dy, dx) = ((row as f32 - cy) / ry, (col as f32 - cx) / rx);
+                        mask[grid.idx(row, col)] = self == Scope::Selection || dy * dy + dx * dx <= 1.0;
+                    }
+                }
+                Some(mask)
+            }
+        }
+    }
+}
+
+/// Runs `generator` on a grid cropped to the mask's bounding box and copies
+/// back only the masked cells. Everything outside the mask is left alone.
+///
+/// Cutting a maze to an arbitrary shape severs corridors, so pockets inside
+/// the mask are tunnelled together afterwards. If the result doesn't touch
+/// open ground outside, one door is opened on its rim.
+pub fn generate_masked(
+    grid: &mut Grid,
+    trace: &mut Trace,
+    generator: Generator,
+    settings: &mut Settings,
+    post: &PostProcess,
+    mask: &[bool],
+) {
+    let Some((r0, c0, r1, c1)) = bounds(grid, mask) else { return };
+    let (width, height) = (c1 - c0 + 1, r1 - r0 + 1);
+    if width < 3 || height < 3 { return; }
+
+    let mut scratch = Grid::new(width, height);
+    let mut sub = trace.child();
+    generator.generate(&mut scratch, &mut sub, settings);
+    post.apply(&mut scratch, &mut sub);
+
+    let grid_w = grid.width;
+    let to_grid = |i: usize| {
+        let idx = (r0 + i / width) * grid_w + c0 + i % width;
+        mask[idx].then_some(idx)
+    };
+    trace.splice(grid, sub, to_grid);
+    for i in 0..width * height {
+        let Some(idx) = to_grid(i) else { continue };
+        let from = *scratch.cell(i);
+        grid.set_wall(idx, from.is_wall);
+        grid.set_terrain(idx, from.cost);
+    }
+
+    let endpoints: Vec<usize> = [grid.start(), grid.end()]
+        .into_iter()
+        .flatten()
+        .filter(|&idx| mask[idx])
+        .collect();
+    for &idx in &endpoints {
+        if grid.cell(idx).is_wall { carve(grid, trace, idx); }
+    }
+    fix_pockets(grid, trace, true, &endpoints, Some(mask));
+    open_door(grid, trace, mask);
+}
+
+/// Inclusive (row, col) corners of the masked area.
+fn bounds(grid: &Grid, mask: &[bool]) -> Option<(usize, usize, usize, usize)> {
+    let mut result: Option<(usize, usize, usize, usize)> = None;
+    for (i, _) in mask.iter().enumerate().filter(|&(_, &inside)| inside) {
+        let (row, col) = (i / grid.width, i % grid.width);
+        result = Some(match result {
+            None => (row, col, row, col),
+            Some((r0, c0, r1, c1)) => (r0.min(row), c0.min(col), r1.max(row), c1.max(col)),
+        });
+    }
+    result
+}
+
+fn open_door(grid: &mut Grid, trace: &mut Trace, mask: &[bool]) {
+    let size = grid.width * grid.height;
+    let connected = (0..size)
+        .any(|i| mask[i] && !grid.cell(i).is_wall && grid.neighbors(i).iter().any(|&n| !mask[n]));
+    if connected { return; }
+
+    // A masked wall with open ground on both sides of the rim.
+    let doors: Vec<usize> = (0..size)
+        .filter(|&i| mask[i] && grid.cell(i).is_wall)
+        .filter(|&i| {
+            let open = around(grid, i).into_iter().filter(|&n| !grid.cell(n).is_wall);
+            let (inside, outside): (Vec<usize>, Vec<usize>) = open.partition(|&n| mask[n]);
+            !inside.is_empty() && !outside.is_empty()
+        })
+        .collect();
+    if !doors.is_empty() {
+        carve(grid, trace, doors[random_index(doors.len())]);
+    }
+}
+
+/// All 4-neighbours, walls included.
+fn around(grid: &Grid, idx: usize) -> Vec<usize> {
+    let (row, col) = (idx / grid.width, idx % grid.width);
+    let mut result = Vec::with_capacity(4);
+    if row > 0                { result.push(idx - grid.width); }
+    if row + 1 < grid.height  { result.push(idx + grid.width); }
+    if col > 0                { result.push(idx - 1); }
+    if col + 1 < grid.width   { result.push(idx + 1); }
+    result
+}
+
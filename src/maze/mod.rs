@@ -1,2 +1,335 @@
 pub mod recursive_backtracker;
-pub mod prims;
\ No newline at end of file
+pub mod prims;
+pub mod kruskal;
+pub mod eller;
+pub mod wilson;
+pub mod aldous_broder;
+pub mod recursive_division;
+pub mod binary_tree;
+pub mod sidewinder;
+pub mod hunt_and_kill;
+pub mod growing_tree;
+pub mod trace;
+pub mod braid;
+pub mod cave;
+pub mod noise;
+pub mod bsp;
+pub mod wfc;
+pub mod mask;
+
+use crate::grid::Grid;
+use growing_tree::Policy;
+use trace::{Event, Trace};
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Generator {
+    RecursiveBacktracker,
+    Prims,
+    Kruskal,
+    Eller,
+    Wilson,
+    AldousBroder,
+    RecursiveDivision,
+    BinaryTree,
+    Sidewinder,
+    HuntAndKill,
+    GrowingTree(Policy),
+    Cave,
+    Terrain,
+    Dungeon,
+    WaveCollapse,
+}
+
+/// Tunable parameters for the generators that have any.
+#[derive(Clone, Default)]
+pub struct Settings {
+    pub cave: cave::Params,
+    pub noise: noise::Params,
+    pub bsp: bsp::Params,
+    pub wfc: wfc::Params,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 17] = [
+        Generator::RecursiveBacktracker,
+        Generator::Prims,
+        Generator::Kruskal,
+        Generator::Eller,
+        Generator::Wilson,
+        Generator::AldousBroder,
+        Generator::RecursiveDivision,
+        Generator::BinaryTree,
+        Generator::Sidewinder,
+        Generator::HuntAndKill,
+        Generator::GrowingTree(Policy::Newest),
+        Generator::GrowingTree(Policy::Random),
+        Generator::GrowingTree(Policy::Oldest),
+        Generator::Cave,
+        Generator::Terrain,
+        Generator::Dungeon,
+        Generator::WaveCollapse,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Generator::RecursiveBacktracker => "Backtracker",
+            Generator::Prims                => "Prim's",
+            Generator::Kruskal              => "Kruskal",
+            Generator::Eller                => "Eller",
+            Generator::Wilson               => "Wilson",
+            Generator::AldousBroder         => "Aldous-Broder",
+            Generator::RecursiveDivision    => "Division",
+            Generator::BinaryTree           => "Binary Tree",
+            Generator::Sidewinder           => "Sidewinder",
+            Generator::HuntAndKill          => "Hunt & Kill",
+            Generator::GrowingTree(Policy::Newest) => "Growing (newest)",
+            Generator::GrowingTree(Policy::Random) => "Growing (random)",
+            Generator::GrowingTree(Policy::Oldest) => "Growing (oldest)",
+            Generator::Cave                 => "Cave",
+            Generator::Terrain              => "Noise Terrain",
+            Generator::Dungeon              => "BSP Dungeon",
+            Generator::WaveCollapse         => "Wave Collapse",
+        }
+    }
+
+    pub fn generate(self, grid: &mut Grid, trace: &mut Trace, settings: &mut Settings) {
+        match self {
+            Generator::RecursiveBacktracker => recursive_backtracker::generate(grid, trace),
+            Generator::Prims                => prims::generate(grid, trace),
+            Generator::Kruskal              => kruskal::generate(grid, trace),
+            Generator::Eller                => eller::generate(grid, trace),
+            Generator::Wilson               => wilson::generate(grid, trace),
+            Generator::AldousBroder         => aldous_broder::generate(grid, trace),
+            Generator::RecursiveDivision    => recursive_division::generate(grid, trace),
+            Generator::BinaryTree           => binary_tree::generate(grid, trace),
+            Generator::Sidewinder           => sidewinder::generate(grid, trace),
+            Generator::HuntAndKill          => hunt_and_kill::generate(grid, trace),
+            Generator::GrowingTree(policy)  => growing_tree::generate(grid, trace, policy),
+            Generator::Cave                 => cave::generate(grid, trace, &settings.cave),
+            Generator::Terrain              => noise::generate(grid, trace, &settings.noise),
+            Generator::Dungeon              => bsp::generate(grid, trace, &settings.bsp),
+            Generator::WaveCollapse         => wfc::generate(grid, trace, &mut settings.wfc),
+        }
+    }
+}
+
+/// Walls off the whole grid. Playback of a traced run starts from here.
+pub fn fill_walls(grid: &mut Grid, trace: &mut Trace) {
+    for i in 0..(grid.width * grid.height) {
+        grid.set_wall(i, true);
+    }
+    trace.begin(grid);
+}
+
+pub fn carve(grid: &mut Grid, trace: &mut Trace, idx: usize) {
+    grid.set_wall(idx, false);
+    trace.push(Event::Carve(idx));
+}
+
+pub fn place_wall(grid: &mut Grid, trace: &mut Trace, idx: usize) {
+    grid.set_wall(idx, true);
+    trace.push(Event::Wall(idx));
+}
+
+/// Maze cells sit on odd rows and columns; everything between them is wall.
+pub fn maze_cells(grid: &Grid) -> Vec<usize> {
+    let mut result = Vec::new();
+    for row in (1..grid.height).step_by(2) {
+        for col in (1..grid.width).step_by(2) {
+            result.push(grid.idx(row, col));
+        }
+    }
+    result
+}
+
+/// Cells two steps away paired with the wall cell between them.
+pub fn maze_neighbors(grid: &Grid, idx: usize) -> Vec<(usize, usize)> {
+    let row = idx / grid.width;
+    let col = idx % grid.width;
+    let mut result = Vec::new();
+
+    if row >= 2          { result.push((grid.idx(row - 2, col), grid.idx(row - 1, col))); }
+    if row + 2 < grid.height { result.push((grid.idx(row + 2, col), grid.idx(row + 1, col))); }
+    if col >= 2          { result.push((grid.idx(row, col - 2), grid.idx(row, col - 1))); }
+    if col + 2 < grid.width  { result.push((grid.idx(row, col + 2), grid.idx(row, col + 1))); }
+
+    result
+}
+
+pub fn random_index(len: usize) -> usize {
+    macroquad::rand::gen_range(0, len)
+}
+
+pub fn shuffle<T>(items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, random_index(i + 1));
+    }
+}
+
+/// Opens the cells the endpoints go on, (1, 1) and the opposite corner,
+/// then runs `fix_pockets` so neither is left in a walled-off pocket.
+pub fn open_corners(grid: &mut Grid, trace: &mut Trace, connect: bool) {
+    if grid.width < 3 || grid.height < 3 { return; }
+    let endpoints = [grid.idx(1, 1), grid.idx(grid.height - 2, grid.width - 2)];
+    for idx in endpoints {
+        if grid.cell(idx).is_wall { carve(grid, trace, idx); }
+    }
+    fix_pockets(grid, trace, connect, &endpoints, None);
+}
+
+/// Keeps the largest open region. Every other pocket is either tunnelled
+/// into it or walled up; pockets holding an endpoint are always tunnelled.
+/// With `within`, only cells inside that mask are looked at or dug through.
+pub fn fix_pockets(grid: &mut Grid, trace: &mut Trace, connect: bool, endpoints: &[usize], within: Option<&[bool]>) {
+    let size = grid.width * grid.height;
+    let allowed = |i: usize| within.is_none_or(|mask| mask[i]);
+    let mut region = vec![usize::MAX; size];
+    let mut sizes = Vec::new();
+
+    for i in 0..size {
+        if grid.cell(i).is_wall || region[i] != usize::MAX || !allowed(i) { continue; }
+        let id = sizes.len();
+        let mut stack = vec![i];
+        let mut count = 0;
+        region[i] = id;
+        while let Some(current) = stack.pop() {
+            count += 1;
+            for neighbor in grid.neighbors(current) {
+                if region[neighbor] == usize::MAX && allowed(neighbor) {
+                    region[neighbor] = id;
+                    stack.push(neighbor);
+                }
+            }
+        }
+        sizes.push(count);
+    }
+
+    let Some(main) = (0..sizes.len()).max_by_key(|&id| sizes[id]) else { return };
+
+    let mut keep = vec![connect; sizes.len()];
+    for &idx in endpoints {
+        if region[idx] != usize::MAX { keep[region[idx]] = true; }
+    }
+
+    for (i, id) in region.iter_mut().enumerate() {
+        if *id != usize::MAX && *id != main && !keep[*id] {
+            place_wall(grid, trace, i);
+            *id = usize::MAX;
+        }
+    }
+
+    // One BFS out of the main cave, through rock, gives every cell a route
+    // back to it. Each kept pocket digs along the route from its nearest cell.
+    let mut parent = vec![usize::MAX; size];
+    let mut dist = vec![usize::MAX; size];
+    let mut queue = std::collections::VecDeque::new();
+    for (i, &id) in region.iter().enumerate() {
+        if id == main {
+            dist[i] = 0;
+            queue.push_back(i);
+        }
+    }
+    while let Some(current) = queue.pop_front() {
+        let (row, col) = (current / grid.width, current % grid.width);
+        let mut next = Vec::with_capacity(4);
+        if row > 0                { next.push(current - grid.width); }
+        if row + 1 < grid.height  { next.push(current + grid.width); }
+        if col > 0                { next.push(current - 1); }
+        if col + 1 < grid.width   { next.push(current + 1); }
+        for n in next {
+            // The outer border is only walked where it is already open.
+            let (r, c) = (n / grid.width, n % grid.width);
+            let border = r == 0 || c == 0 || r + 1 == grid.height || c + 1 == grid.width;
+            if dist[n] == usize::MAX && allowed(n) && (!border || !grid.cell(n).is_wall) {
+                dist[n] = dist[current] + 1;
+                parent[n] = current;
+                queue.push_back(n);
+            }
+        }
+    }
+
+    let mut nearest = vec![usize::MAX; sizes.len()];
+    for i in 0..size {
+        let id = region[i];
+        if id == usize::MAX || id == main { continue; }
+        if nearest[id] == usize::MAX || dist[i] < dist[nearest[id]] { nearest[id] = i; }
+    }
+    // Under a mask some pockets may have no route at all; they stay as they are.
+    for start in nearest.into_iter().filter(|&i| i != usize::MAX && dist[i] != usize::MAX) {
+        let mut current = start;
+        while dist[current] > 0 {
+            if grid.cell(current).is_wall { carve(grid, trace, current); }
+            current = parent[current];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Checks that the open cells form a spanning tree over `maze_cells`:
+    /// every maze cell is open, the only other open cells are links between
+    /// two of them, there is one link fewer than cells, and all connect.
+    fn assert_spanning_tree(generator: Generator, width: usize, height: usize, seed: u64) {
+        macroquad::rand::srand(seed);
+        let mut grid = Grid::new(width, height);
+        generator.generate(&mut grid, &mut Trace::off(), &mut Settings::default());
+        let name = format!("{} {width}x{height} seed {seed}", generator.name());
+
+        let cells = maze_cells(&grid);
+        assert!(cells.iter().all(|&i| !grid.cell(i).is_wall), "{name}: a maze cell is walled");
+        let mut links = 0;
+        for i in 0..width * height {
+            let (row, col) = (i / width, i % width);
+            if grid.cell(i).is_wall || (row % 2 == 1 && col % 2 == 1) { continue; }
+            assert!(row % 2 != col % 2, "{name}: ({row}, {col}) is open but not a link");
+            links += 1;
+        }
+        assert_eq!(cells.len(), links + 1, "{name}: not a tree");
+
+        let mut seen = vec![false; width * height];
+        let mut queue = VecDeque::from([cells[0]]);
+        seen[cells[0]] = true;
+        let mut reached = 1;
+        while let Some(current) = queue.pop_front() {
+            for next in grid.neighbors(current) {
+                if !seen[next] {
+                    seen[next] = true;
+                    reached += 1;
+                    queue.push_back(next);
+                }
+            }
+        }
+        assert_eq!(reached, cells.len() + links, "{name}: not connected");
+    }
+
+    fn assert_spanning_trees(generators: &[Generator]) {
+        for &generator in generators {
+            for (width, height) in [(3, 3), (5, 9), (21, 15), (31, 31), (41, 7)] {
+                for seed in 1..6 {
+                    assert_spanning_tree(generator, width, height, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_and_set_based_generators_make_spanning_trees() {
+        assert_spanning_trees(&[Generator::Kruskal, Generator::Eller, Generator::Wilson, Generator::AldousBroder]);
+    }
+
+    #[test]
+    fn wall_adding_and_biased_generators_make_spanning_trees() {
+        assert_spanning_trees(&[
+            Generator::RecursiveDivision,
+            Generator::BinaryTree,
+            Generator::Sidewinder,
+            Generator::HuntAndKill,
+            Generator::GrowingTree(Policy::Newest),
+            Generator::GrowingTree(Policy::Random),
+            Generator::GrowingTree(Policy::Oldest),
+        ]);
+    }
+}
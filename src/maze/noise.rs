@@ -0,0 +1,96 @@
+// src/maze/noise.rs
+use crate::grid::{Grid, COST_MUD, COST_NORMAL, COST_WATER};
+use super::{open_corners, place_wall};
+use super::trace::{Event, Trace};
+
+/// Fractal value noise mapped to terrain bands. The noise is rescaled to
+/// 0..1 over the whole map, so thresholds read as "low" to "high" ground.
+#[derive(Clone, Copy)]
+pub struct Params {
+    pub seed: u32,
+    /// Size of the largest features, in cells.
+    pub scale: f32,
+    pub octaves: usize,
+    /// Below this: water.
+    pub water: f32,
+    /// Below this (and above water): mud.
+    pub mud: f32,
+    /// At or above this: wall.
+    pub wall: f32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { seed: 1, scale: 16.0, octaves: 4, water: 0.25, mud: 0.4, wall: 0.75 }
+    }
+}
+
+pub fn generate(grid: &mut Grid, trace: &mut Trace, params: &Params) {
+    trace.begin(grid);
+
+    let size = grid.width * grid.height;
+    let values: Vec<f32> = (0..size)
+        .map(|i| fractal((i % grid.width) as f32, (i / grid.width) as f32, params))
+        .collect();
+    let min = values.iter().copied().fold(f32::MAX, f32::min);
+    let max = values.iter().copied().fold(f32::MIN, f32::max);
+    let range = (max - min).max(f32::EPSILON);
+
+    for (i, value) in values.into_iter().enumerate() {
+        let n = (value - min) / range;
+        if n >= params.wall {
+            place_wall(grid, trace, i);
+            continue;
+        }
+        let cost = if n < params.water { COST_WATER }
+            else if n < params.mud      { COST_MUD }
+            else                        { COST_NORMAL };
+        if cost != COST_NORMAL {
+            grid.set_terrain(i, cost);
+            trace.push(Event::Terrain(i, cost));
+        }
+    }
+
+    // Endpoints always sit at (1, 1) and the opposite corner.
+    open_corners(grid, trace, true);
+}
+
+fn fractal(x: f32, y: f32, params: &Params) -> f32 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0 / params.scale.max(1.0);
+    for octave in 0..params.octaves.max(1) {
+        let seed = params.seed.wrapping_add(octave as u32 * 7919);
+        total += value_noise(x * frequency, y * frequency, seed) * amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    total
+}
+
+/// Smoothly interpolated random values on an integer lattice.
+fn value_noise(x: f32, y: f32, seed: u32) -> f32 {
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let (tx, ty) = (smooth(x - x0 as f32), smooth(y - y0 as f32));
+
+    let top = lerp(lattice(x0, y0, seed), lattice(x0 + 1, y0, seed), tx);
+    let bottom = lerp(lattice(x0, y0 + 1, seed), lattice(x0 + 1, y0 + 1, seed), tx);
+    lerp(top, bottom, ty)
+}
+
+fn lattice(x: i32, y: i32, seed: u32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x27d4_eb2d)
+        ^ (y as u32).wrapping_mul(0x1656_67b1)
+        ^ seed.wrapping_mul(0x9e37_79b9);
+    h = (h ^ (h >> 15)).wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 13;
+    (h & 0x00ff_ffff) as f32 / 16_777_216.0
+}
+
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
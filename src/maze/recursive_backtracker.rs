@@ -1,22 +1,22 @@
 // src/maze/recursive_backtracker.rs
 use crate::grid::Grid;
+use super::{carve, fill_walls, maze_neighbors, random_index};
+use super::trace::{Event, Trace};
 
-pub fn generate(grid: &mut Grid) {
-    for i in 0..(grid.width * grid.height) {
-        let cell = grid.cell_mut(i);
-        cell.is_wall = true;
-        cell.cost = 0;
-    }
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
 
     let mut visited = vec![false; grid.width * grid.height];
     let mut stack = Vec::new();
 
     let start = grid.idx(1, 1);
     visited[start] = true;
-    carve(grid, start);
+    carve(grid, trace, start);
     stack.push(start);
+    trace.push(Event::Mark(start));
 
     while let Some(&current) = stack.last() {
+        trace.push(Event::Head(current));
         let unvisited = maze_neighbors(grid, current)
             .into_iter()
             .filter(|&(neighbor, _)| !visited[neighbor])
@@ -24,35 +24,14 @@ pub fn generate(grid: &mut Grid) {
 
         if unvisited.is_empty() {
             stack.pop();
+            trace.push(Event::Unmark(current));
         } else {
             let (neighbor, wall_between) = unvisited[random_index(unvisited.len())];
-            carve(grid, wall_between);
-            carve(grid, neighbor);
+            carve(grid, trace, wall_between);
+            carve(grid, trace, neighbor);
             visited[neighbor] = true;
             stack.push(neighbor);
+            trace.push(Event::Mark(neighbor));
         }
     }
 }
-
-fn carve(grid: &mut Grid, idx: usize) {
-    let cell = grid.cell_mut(idx);
-    cell.is_wall = false;
-    cell.cost = 1;
-}
-
-fn maze_neighbors(grid: &Grid, idx: usize) -> Vec<(usize, usize)> {
-    let row = idx / grid.width;
-    let col = idx % grid.width;
-    let mut result = Vec::new();
-
-    if row >= 2          { result.push((grid.idx(row - 2, col), grid.idx(row - 1, col))); }
-    if row + 2 < grid.height { result.push((grid.idx(row + 2, col), grid.idx(row + 1, col))); }
-    if col >= 2          { result.push((grid.idx(row, col - 2), grid.idx(row, col - 1))); }
-    if col + 2 < grid.width  { result.push((grid.idx(row, col + 2), grid.idx(row, col + 1))); }
-
-    result
-}
-
-fn random_index(len: usize) -> usize {
-    macroquad::rand::gen_range(0, len)
-}
\ No newline at end of file
@@ -0,0 +1,61 @@
+// src/maze/recursive_division.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, place_wall, random_index};
+use super::trace::{Event, Trace};
+
+/// Region of open space, inclusive, with corners on odd maze-cell coordinates.
+struct Region {
+    top: usize,
+    left: usize,
+    bottom: usize,
+    right: usize,
+}
+
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
+    if grid.width < 2 || grid.height < 2 { return; }
+
+    // Open the whole maze area, then add walls back in.
+    let bottom = (grid.height - 2) | 1;
+    let right = (grid.width - 2) | 1;
+    for row in 1..=bottom {
+        for col in 1..=right {
+            carve(grid, trace, grid.idx(row, col));
+        }
+    }
+    trace.begin(grid);
+
+    let mut stack = vec![Region { top: 1, left: 1, bottom, right }];
+    while let Some(region) = stack.pop() {
+        let cells_high = (region.bottom - region.top) / 2 + 1;
+        let cells_wide = (region.right - region.left) / 2 + 1;
+        if cells_high < 2 || cells_wide < 2 { continue; }
+
+        let horizontal = match cells_high.cmp(&cells_wide) {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less    => false,
+            std::cmp::Ordering::Equal   => random_index(2) == 0,
+        };
+
+        if horizontal {
+            // Wall on an even row, passage on an odd column.
+            let wall_row = region.top + 1 + 2 * random_index(cells_high - 1);
+            let gap = region.left + 2 * random_index(cells_wide);
+            for col in region.left..=region.right {
+                if col != gap { place_wall(grid, trace, grid.idx(wall_row, col)); }
+            }
+            trace.push(Event::Head(grid.idx(wall_row, gap)));
+            stack.push(Region { top: region.top, left: region.left, bottom: wall_row - 1, right: region.right });
+            stack.push(Region { top: wall_row + 1, left: region.left, bottom: region.bottom, right: region.right });
+        } else {
+            let wall_col = region.left + 1 + 2 * random_index(cells_wide - 1);
+            let gap = region.top + 2 * random_index(cells_high);
+            for row in region.top..=region.bottom {
+                if row != gap { place_wall(grid, trace, grid.idx(row, wall_col)); }
+            }
+            trace.push(Event::Head(grid.idx(gap, wall_col)));
+            stack.push(Region { top: region.top, left: region.left, bottom: region.bottom, right: wall_col - 1 });
+            stack.push(Region { top: region.top, left: wall_col + 1, bottom: region.bottom, right: region.right });
+        }
+    }
+}
@@ -0,0 +1,36 @@
+// src/maze/sidewinder.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, random_index};
+use super::trace::{Event, Trace};
+
+/// Row by row: extend a run eastwards, then close it by opening one cell
+/// of the run to the north. The top row is a single corridor.
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
+
+    for row in (1..grid.height).step_by(2) {
+        let mut run: Vec<usize> = Vec::new();
+        for col in (1..grid.width).step_by(2) {
+            let cell = grid.idx(row, col);
+            trace.push(Event::Head(cell));
+            carve(grid, trace, cell);
+            run.push(cell);
+            trace.push(Event::Mark(cell));
+
+            let at_east = col + 2 >= grid.width;
+            let at_top = row == 1;
+            let close_run = at_east || (!at_top && random_index(2) == 0);
+
+            if close_run {
+                if !at_top {
+                    let member = run[random_index(run.len())];
+                    carve(grid, trace, member - grid.width);
+                }
+                for &member in &run { trace.push(Event::Unmark(member)); }
+                run.clear();
+            } else {
+                carve(grid, trace, cell + 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// src/maze/trace.rs
+use crate::grid::Grid;
+use super::{carve, place_wall};
+
+/// One visible change made by a generator.
+#[derive(Clone, Copy)]
+pub enum Event {
+    Carve(usize),
+    Wall(usize),
+    /// The cell the generator is working from.
+    Head(usize),
+    /// Cell joined the generator's working set (frontier, stack, run...).
+    Mark(usize),
+    /// Cell left the working set.
+    Unmark(usize),
+    Terrain(usize, u16),
+    /// A solver added the cell to its open set.
+    Open(usize),
+    /// A solver walked through or expanded the cell.
+    Visit(usize),
+    /// The cell is on a solver's final route.
+    Path(usize),
+}
+
+impl Event {
+    /// The same event moved to another cell index, if `map` keeps it.
+    fn remap(self, map: impl Fn(usize) -> Option<usize>) -> Option<Event> {
+        Some(match self {
+            Event::Carve(idx)  => Event::Carve(map(idx)?),
+            Event::Wall(idx)   => Event::Wall(map(idx)?),
+            Event::Head(idx)   => Event::Head(map(idx)?),
+            Event::Mark(idx)   => Event::Mark(map(idx)?),
+            Event::Unmark(idx) => Event::Unmark(map(idx)?),
+            Event::Terrain(idx, cost) => Event::Terrain(map(idx)?, cost),
+            Event::Open(idx)   => Event::Open(map(idx)?),
+            Event::Visit(idx)  => Event::Visit(map(idx)?),
+            Event::Path(idx)   => Event::Path(map(idx)?),
+        })
+    }
+}
+
+/// Records what a generator or solver does so it can be replayed step by step.
+/// A trace made with `off()` ignores everything, so instant generation
+/// pays nothing for it.
+pub struct Trace {
+    recording: bool,
+    base: Option<Grid>,
+    events: Vec<Event>,
+}
+
+impl Trace {
+    pub fn off() -> Self {
+        Self { recording: false, base: None, events: Vec::new() }
+    }
+
+    pub fn recording() -> Self {
+        Self { recording: true, base: None, events: Vec::new() }
+    }
+
+    /// A fresh trace that records if and only if this one does.
+    pub fn child(&self) -> Self {
+        Self { recording: self.recording, base: None, events: Vec::new() }
+    }
+
+    /// Snapshot the grid playback starts from and drop anything recorded
+    /// before it (e.g. the initial fill).
+    pub fn begin(&mut self, grid: &Grid) {
+        if self.recording {
+            self.base = Some(grid.clone());
+            self.events.clear();
+        }
+    }
+
+    pub fn push(&mut self, event: Event) {
+        if self.recording {
+            self.events.push(event);
+        }
+    }
+
+    /// Takes over a trace recorded on a different grid, e.g. a cropped copy.
+    /// `map` turns a cell index there into one in `grid`, or drops it.
+    /// Playback starts from `grid` with the mapped cells of `sub`'s snapshot.
+    pub fn splice(&mut self, grid: &Grid, sub: Trace, map: impl Fn(usize) -> Option<usize>) {
+        if !self.recording { return; }
+        let mut base = grid.clone();
+        if let Some(sub_base) = &sub.base {
+            for i in 0..sub_base.width * sub_base.height {
+                let Some(idx) = map(i) else { continue };
+                let from = sub_base.cell(i);
+                base.set_wall(idx, from.is_wall);
+                base.set_terrain(idx, from.cost);
+            }
+        }
+        self.base = Some(base);
+        self.events = sub.events.into_iter().filter_map(|event| event.remap(&map)).collect();
+    }
+
+    /// Rewinds `grid` to the recorded starting point and returns a playback
+    /// that walks it forward to its current state.
+    pub fn into_playback(self, grid: &mut Grid) -> Option<Playback> {
+        let base = self.base?;
+        let finished = std::mem::replace(grid, base);
+        Some(Playback {
+            marked: vec![false; grid.width * grid.height],
+            finished,
+            events: self.events,
+            cursor: 0,
+            head: None,
+            last: None,
+            paused: false,
+        })
+    }
+}
+
+pub struct Playback {
+    finished: Grid,
+    events: Vec<Event>,
+    cursor: usize,
+    pub head: Option<usize>,
+    /// The event applied most recently.
+    pub last: Option<Event>,
+    pub marked: Vec<bool>,
+    pub paused: bool,
+}
+
+impl Playback {
+    /// Applies up to `count` events to `grid`.
+    pub fn advance(&mut self, grid: &mut Grid, count: usize) {
+        let end = (self.cursor + count).min(self.events.len());
+        while self.cursor < end {
+            self.apply(grid);
+        }
+    }
+
+    /// Applies events until the head has moved `steps` more times. Runs that
+    /// record different amounts per step still move side by side this way.
+    pub fn advance_steps(&mut self, grid: &mut Grid, steps: usize) {
+        let mut moved = 0;
+        while self.cursor < self.events.len() {
+            if let Event::Head(_) = self.events[self.cursor] {
+                if moved == steps { break; }
+                moved += 1;
+            }
+            self.apply(grid);
+        }
+    }
+
+    fn apply(&mut self, grid: &mut Grid) {
+        match self.events[self.cursor] {
+            Event::Carve(idx)  => carve(grid, &mut Trace::off(), idx),
+            Event::Wall(idx)   => place_wall(grid, &mut Trace::off(), idx),
+            Event::Head(idx)   => self.head = Some(idx),
+            Event::Mark(idx)   => self.marked[idx] = true,
+            Event::Unmark(idx) => self.marked[idx] = false,
+            Event::Terrain(idx, cost) => grid.set_terrain(idx, cost),
+            Event::Open(idx)   => grid.cell_mut(idx).is_open = true,
+            Event::Visit(idx)  => {
+                let cell = grid.cell_mut(idx);
+                cell.is_visited = true;
+                cell.is_open = false;
+            }
+            Event::Path(idx)   => grid.cell_mut(idx).is_path = true,
+        }
+        self.last = Some(self.events[self.cursor]);
+        self.cursor += 1;
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.cursor >= self.events.len()
+    }
+
+    pub fn progress(&self) -> (usize, usize) {
+        (self.cursor, self.events.len())
+    }
+
+    /// Jumps to the end, leaving `grid` exactly as the generator produced it.
+    pub fn finish(self, grid: &mut Grid) {
+        *grid = self.finished;
+    }
+}
@@ -0,0 +1,385 @@
+// src/maze/wfc.rs
+use std::collections::HashMap;
+use crate::grid::{Cell, Grid, COST_MUD, COST_NORMAL, COST_WATER};
+use super::{open_corners, place_wall};
+use super::trace::{Event, Trace};
+
+// Tile classes read from and written to cells.
+const FLOOR: u8 = 0;
+const WALL: u8 = 1;
+const MUD: u8 = 2;
+const WATER: u8 = 3;
+
+// Overlap offsets as (d_row, d_col): up, right, down, left.
+const DIRS: [(i32, i32); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+const OPPOSITE: [usize; 4] = [2, 3, 0, 1];
+
+// Memory guard for the wave. Each (position, pattern) pair costs four
+// support counters, a possible flag and at most one pending ban.
+const MAX_WAVE_BYTES: usize = 32 << 20;
+const PAIR_BYTES: usize = size_of::<[usize; 4]>() + size_of::<bool>() + size_of::<(usize, usize)>();
+
+/// A rectangle of tiles copied out of a hand-drawn grid.
+#[derive(Clone)]
+pub struct Sample {
+    pub width: usize,
+    pub height: usize,
+    tiles: Vec<u8>,
+}
+
+impl Sample {
+    /// Copies the inclusive rectangle between two cells.
+    pub fn from_region(grid: &Grid, a: usize, b: usize) -> Self {
+        let (r0, r1) = ((a / grid.width).min(b / grid.width), (a / grid.width).max(b / grid.width));
+        let (c0, c1) = ((a % grid.width).min(b % grid.width), (a % grid.width).max(b % grid.width));
+        let mut tiles = Vec::with_capacity((r1 - r0 + 1) * (c1 - c0 + 1));
+        for row in r0..=r1 {
+            for col in c0..=c1 {
+                tiles.push(tile_of(grid.cell(grid.idx(row, col))));
+            }
+        }
+        Self { width: c1 - c0 + 1, height: r1 - r0 + 1, tiles }
+    }
+}
+
+#[derive(Clone)]
+pub struct Params {
+    pub sample: Option<Sample>,
+    /// Pattern size; output matches the sample in every `n` x `n` window.
+    pub n: usize,
+    pub seed: u32,
+    pub retries: u32,
+    /// Outcome of the last run, shown under the options.
+    pub report: String,
+    /// Whether the last run wrote a map; `report` says why not.
+    pub generated: bool,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { sample: None, n: 3, seed: 1, retries: 10, report: "Select a region".to_string(), generated: false }
+    }
+}
+
+/// Overlapping Wave Function Collapse. Every `n` x `n` window of the output
+/// appears in the sample. A contradiction restarts with the next seed.
+pub fn generate(grid: &mut Grid, trace: &mut Trace, params: &mut Params) {
+    trace.begin(grid);
+    params.generated = false;
+
+    let Some(sample) = &params.sample else {
+        params.report = "No sample taken".to_string();
+        return;
+    };
+    let n = params.n;
+    if sample.width < n || sample.height < n || grid.width < n || grid.height < n {
+        params.report = format!("Sample and grid need {n}x{n}");
+        return;
+    }
+
+    let model = Model::new(sample, n);
+    let positions = (grid.width - n + 1) * (grid.height - n + 1);
+    if positions.saturating_mul(model.patterns.len()).saturating_mul(PAIR_BYTES) > MAX_WAVE_BYTES {
+        params.report = format!("Too big: {} patterns", model.patterns.len());
+        return;
+    }
+
+    for attempt in 0..params.retries.max(1) {
+        let mut rng = Rng(u64::from(params.seed.wrapping_add(attempt)));
+        let Some((chosen, order)) = model.solve(grid.width, grid.height, &mut rng) else { continue };
+
+        write_output(grid, trace, &model, &chosen, &order);
+        open_corners(grid, trace, true);
+        params.report = format!("{} patterns, try {}", model.patterns.len(), attempt + 1);
+        params.generated = true;
+        return;
+    }
+
+    params.report = format!("Contradiction x{}", params.retries.max(1));
+}
+
+fn tile_of(cell: &Cell) -> u8 {
+    if cell.is_wall                { WALL }
+    else if cell.cost == COST_MUD   { MUD }
+    else if cell.cost == COST_WATER { WATER }
+    else                            { FLOOR }
+}
+
+fn write_output(grid: &mut Grid, trace: &mut Trace, model: &Model, chosen: &[usize], order: &[usize]) {
+    let n = model.n;
+    let pos_w = grid.width - n + 1;
+    let pos_h = grid.height - n + 1;
+
+    // Replay cells in the order their windows were decided.
+    let mut rank = vec![usize::MAX; chosen.len()];
+    for (i, &pos) in order.iter().enumerate() { rank[pos] = i; }
+    let mut cells: Vec<(usize, usize)> = (0..grid.width * grid.height)
+        .map(|i| {
+            let (row, col) = (i / grid.width, i % grid.width);
+            let pos = row.min(pos_h - 1) * pos_w + col.min(pos_w - 1);
+            (i, pos)
+        })
+        .collect();
+    cells.sort_by_key(|&(_, pos)| rank[pos]);
+
+    for (i, pos) in cells {
+        let (row, col) = (i / grid.width, i % grid.width);
+        let (dr, dc) = (row - row.min(pos_h - 1), col - col.min(pos_w - 1));
+        trace.push(Event::Head(i));
+        match model.patterns[chosen[pos]][dr * n + dc] {
+            WALL  => place_wall(grid, trace, i),
+            MUD   => { grid.set_terrain(i, COST_MUD); trace.push(Event::Terrain(i, COST_MUD)); }
+            WATER => { grid.set_terrain(i, COST_WATER); trace.push(Event::Terrain(i, COST_WATER)); }
+            _     => { grid.set_terrain(i, COST_NORMAL); }
+        }
+    }
+}
+
+struct Model {
+    n: usize,
+    patterns: Vec<Vec<u8>>,
+    weights: Vec<f64>,
+    /// `propagator[d][p]`: patterns allowed one step in direction `d` of `p`.
+    propagator: [Vec<Vec<usize>>; 4],
+}
+
+impl Model {
+    fn new(sample: &Sample, n: usize) -> Self {
+        let mut index: HashMap<Vec<u8>, usize> = HashMap::new();
+        let mut patterns = Vec::new();
+        let mut weights = Vec::new();
+
+        for row in 0..=sample.height - n {
+            for col in 0..=sample.width - n {
+                let mut pattern = Vec::with_capacity(n * n);
+                for dr in 0..n {
+                    let start = (row + dr) * sample.width + col;
+                    pattern.extend_from_slice(&sample.tiles[start..start + n]);
+                }
+                let id = *index.entry(pattern.clone()).or_insert_with(|| {
+                    patterns.push(pattern);
+                    weights.push(0.0);
+                    patterns.len() - 1
+                });
+                weights[id] += 1.0;
+            }
+        }
+
+        let propagator = std::array::from_fn(|d| {
+            let (dr, dc) = DIRS[d];
+            (0..patterns.len())
+                .map(|p| {
+                    (0..patterns.len())
+                        .filter(|&q| agrees(&patterns[p], &patterns[q], dr, dc, n))
+                        .collect()
+                })
+                .collect()
+        });
+
+        Self { n, patterns, weights, propagator }
+    }
+
+    /// Returns the chosen pattern per position and the order positions were
+    /// decided in, or `None` on contradiction.
+    fn solve(&self, width: usize, height: usize, rng: &mut Rng) -> Option<(Vec<usize>, Vec<usize>)> {
+        let count = self.patterns.len();
+        let pos_w = width - self.n + 1;
+        let pos_h = height - self.n + 1;
+        let positions = pos_w * pos_h;
+        let mut wave = Wave::new(self, positions);
+
+        loop {
+            // Observe: lowest-entropy undecided position, ties broken by noise.
+            let mut best = None;
+            let mut best_entropy = f64::MAX;
+            for pos in 0..positions {
+                match wave.remaining[pos] {
+                    0 => return None,
+                    1 => continue,
+                    _ => {}
+                }
+                let entropy = wave.sum_w[pos].ln() - wave.sum_wlogw[pos] / wave.sum_w[pos];
+                let noisy = entropy + rng.below(1000) as f64 * 1e-7;
+                if noisy < best_entropy {
+                    best_entropy = noisy;
+                    best = Some(pos);
+                }
+            }
+
+            let Some(pos) = best else {
+                let chosen = (0..positions)
+                    .map(|pos| (0..count).find(|&p| wave.possible[pos * count + p]).unwrap_or(0))
+                    .collect();
+                return Some((chosen, wave.order));
+            };
+
+            let mut roll = rng.below(1_000_000) as f64 / 1_000_000.0 * wave.sum_w[pos];
+            let mut pick = 0;
+            for p in 0..count {
+                if !wave.possible[pos * count + p] { continue; }
+                pick = p;
+                roll -= self.weights[p];
+                if roll <= 0.0 { break; }
+            }
+            for p in 0..count {
+                if p != pick && wave.possible[pos * count + p] {
+                    wave.ban(pos, p, &self.weights);
+                }
+            }
+
+            // Propagate bans to neighbours until nothing changes.
+            while let Some((pos, p)) = wave.stack.pop() {
+                let (row, col) = ((pos / pos_w) as i32, (pos % pos_w) as i32);
+                for (d, &(dr, dc)) in DIRS.iter().enumerate() {
+                    let (r2, c2) = (row + dr, col + dc);
+                    if r2 < 0 || c2 < 0 || r2 >= pos_h as i32 || c2 >= pos_w as i32 { continue; }
+                    let pos2 = r2 as usize * pos_w + c2 as usize;
+                    for &q in &self.propagator[d][p] {
+                        let counter = &mut wave.compatible[pos2 * count + q][d];
+                        *counter -= 1;
+                        if *counter == 0 && wave.possible[pos2 * count + q] {
+                            wave.ban(pos2, q, &self.weights);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Remaining patterns per output position, with the running sums needed for
+/// entropy and the AC-4 support counters used during propagation.
+struct Wave {
+    count: usize,
+    possible: Vec<bool>,
+    remaining: Vec<usize>,
+    sum_w: Vec<f64>,
+    sum_wlogw: Vec<f64>,
+    compatible: Vec<[usize; 4]>,
+    order: Vec<usize>,
+    stack: Vec<(usize, usize)>,
+}
+
+impl Wave {
+    fn new(model: &Model, positions: usize) -> Self {
+        let count = model.patterns.len();
+        let total_w: f64 = model.weights.iter().sum();
+        let total_wlogw: f64 = model.weights.iter().map(|w| w * w.ln()).sum();
+        let support: Vec<[usize; 4]> = (0..count)
+            .map(|p| std::array::from_fn(|d| model.propagator[OPPOSITE[d]][p].len()))
+            .collect();
+
+        Self {
+            count,
+            possible: vec![true; positions * count],
+            remaining: vec![count; positions],
+            sum_w: vec![total_w; positions],
+            sum_wlogw: vec![total_wlogw; positions],
+            compatible: (0..positions).flat_map(|_| support.iter().copied()).collect(),
+            order: Vec::with_capacity(positions),
+            stack: Vec::new(),
+        }
+    }
+
+    fn ban(&mut self, pos: usize, p: usize, weights: &[f64]) {
+        self.possible[pos * self.count + p] = false;
+        self.remaining[pos] -= 1;
+        self.sum_w[pos] -= weights[p];
+        self.sum_wlogw[pos] -= weights[p] * weights[p].ln();
+        if self.remaining[pos] == 1 { self.order.push(pos); }
+        self.stack.push((pos, p));
+    }
+}
+
+/// Whether `q` placed at offset (dr, dc) from `p` matches it on the overlap.
+fn agrees(p: &[u8], q: &[u8], dr: i32, dc: i32, n: usize) -> bool {
+    let n = n as i32;
+    for r in dr.max(0)..(n + dr).min(n) {
+        for c in dc.max(0)..(n + dc).min(n) {
+            if p[(r * n + c) as usize] != q[((r - dr) * n + (c - dc)) as usize] {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+/// SplitMix64. Each attempt seeds its own, so a run repeats for the same
+/// seed without reseeding the `macroquad::rand` sequence the rest of the
+/// app shares.
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        ((z ^ (z >> 31)) % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sample drawn from text rows, `#` walls and `m` mud.
+    fn sample(rows: &[&str]) -> Sample {
+        let grid = Grid::from_text(&rows.join("\n")).unwrap();
+        Sample::from_region(&grid, 0, grid.width * grid.height - 1)
+    }
+
+    fn run(params: &mut Params, width: usize, height: usize) -> String {
+        let mut grid = Grid::new(width, height);
+        generate(&mut grid, &mut Trace::off(), params);
+        grid.to_text()
+    }
+
+    fn rooms() -> Params {
+        let rows = ["#########", "#...#...#", "#.m.#...#", "#.......#", "#...#.m.#", "#...#...#", "#########"];
+        Params { sample: Some(sample(&rows)), n: 2, seed: 7, ..Params::default() }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_map() {
+        let mut params = rooms();
+        let first = run(&mut params, 30, 20);
+        assert!(params.generated, "{}", params.report);
+        assert_eq!(run(&mut params, 30, 20), first);
+        params.seed = 8;
+        assert_ne!(run(&mut params, 30, 20), first);
+    }
+
+    #[test]
+    fn leaves_the_shared_rng_alone() {
+        macroquad::rand::srand(99);
+        let expected = macroquad::rand::rand();
+        macroquad::rand::srand(99);
+        run(&mut rooms(), 30, 20);
+        assert_eq!(macroquad::rand::rand(), expected);
+    }
+
+    #[test]
+    fn reports_contradictions() {
+        // Two 3x3 patterns; the second has nothing that fits to its right
+        // and neither has anything above or below.
+        let mut params = Params { sample: Some(sample(&["....", ".#..", "...."])), retries: 4, ..Params::default() };
+        let before = run(&mut Params::default(), 10, 10);
+        assert_eq!(run(&mut params, 10, 10), before);
+        assert!(!params.generated);
+        assert_eq!(params.report, "Contradiction x4");
+    }
+
+    #[test]
+    fn reports_missing_sample_and_oversized_waves() {
+        let mut params = Params::default();
+        run(&mut params, 10, 10);
+        assert!(!params.generated);
+        assert_eq!(params.report, "No sample taken");
+
+        let mut params = rooms();
+        run(&mut params, 1000, 1000);
+        assert!(!params.generated);
+        assert!(params.report.starts_with("Too big"), "{}", params.report);
+    }
+}
@@ -0,0 +1,45 @@
+// src/maze/wilson.rs
+use crate::grid::Grid;
+use super::{carve, fill_walls, maze_cells, maze_neighbors, random_index};
+use super::trace::{Event, Trace};
+
+pub fn generate(grid: &mut Grid, trace: &mut Trace) {
+    fill_walls(grid, trace);
+
+    let cells = maze_cells(grid);
+    if cells.is_empty() { return; }
+
+    let mut in_maze = vec![false; grid.width * grid.height];
+    // Direction taken out of each cell on the current walk, as (next, wall).
+    // Overwriting it on revisits is what erases loops.
+    let mut exit = vec![(usize::MAX, usize::MAX); grid.width * grid.height];
+
+    let root = cells[random_index(cells.len())];
+    in_maze[root] = true;
+    carve(grid, trace, root);
+
+    for &walk_start in &cells {
+        if in_maze[walk_start] { continue; }
+
+        let mut walked = Vec::new();
+        let mut current = walk_start;
+        while !in_maze[current] {
+            trace.push(Event::Head(current));
+            trace.push(Event::Mark(current));
+            walked.push(current);
+            let options = maze_neighbors(grid, current);
+            exit[current] = options[random_index(options.len())];
+            current = exit[current].0;
+        }
+        for idx in walked { trace.push(Event::Unmark(idx)); }
+
+        let mut current = walk_start;
+        while !in_maze[current] {
+            let (next, wall) = exit[current];
+            in_maze[current] = true;
+            carve(grid, trace, current);
+            carve(grid, trace, wall);
+            current = next;
+        }
+    }
+}
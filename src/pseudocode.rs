@@ -0,0 +1,229 @@
+// src/pseudocode.rs
+use crate::maze::trace::Event;
+
+const GUIDE: &str = include_str!("algorithms/algorithm-guide.md");
+
+/// Pseudocode for one solver, and which line each kind of traced event
+/// belongs to, so the replay can point at the line it is on.
+pub struct Listing {
+    /// File name the algorithm guide files the algorithm under.
+    pub file: &'static str,
+    pub lines: &'static [&'static str],
+    /// What the open set is called, if the solver has one.
+    pub frontier: Option<&'static str>,
+    head: Option<usize>,
+    open: Option<usize>,
+    visit: Option<usize>,
+    /// Trail pushes and loop cuts of the walkers.
+    trail: Option<usize>,
+    path: Option<usize>,
+}
+
+impl Listing {
+    pub fn line(&self, event: Event) -> Option<usize> {
+        match event {
+            Event::Head(_)                     => self.head,
+            Event::Open(_)                     => self.open,
+            Event::Visit(_)                    => self.visit,
+            Event::Mark(_) | Event::Unmark(_)  => self.trail,
+            Event::Path(_)                     => self.path,
+            _                                  => None,
+        }
+    }
+
+    /// Tagline and opening paragraph of the algorithm's guide section.
+    pub fn guide(&self) -> Option<(&'static str, &'static str)> {
+        let marker = format!("**File:** `{}`", self.file);
+        let mut lines = GUIDE.lines().skip_while(|line| !line.starts_with(&marker));
+        let logic = lines.next()?.split("**Logic:** ").nth(1)?;
+        let intro = lines.find(|line| !line.trim().is_empty())?;
+        Some((logic, intro))
+    }
+}
+
+const BFS: Listing = Listing {
+    file: "bfs.rs",
+    lines: &[
+        "queue <- [start]",
+        "while queue is not empty:",
+        "    current <- queue.pop_front()",
+        "    if current = end: return path",
+        "    close current",
+        "    for each neighbour n of current:",
+        "        if n not seen yet:",
+        "            parent[n] <- current",
+        "            queue.push_back(n)",
+        "return no path",
+    ],
+    frontier: Some("queue"),
+    head: Some(2),
+    open: Some(8),
+    visit: Some(4),
+    trail: None,
+    path: Some(3),
+};
+
+const DFS: Listing = Listing {
+    file: "dfs.rs",
+    lines: &[
+        "stack <- [start]",
+        "while stack is not empty:",
+        "    current <- stack.pop()",
+        "    if current closed: continue",
+        "    if current = end: return path",
+        "    close current",
+        "    for each neighbour n of current:",
+        "        if n not closed:",
+        "            parent[n] <- current",
+        "            stack.push(n)",
+        "return no path",
+    ],
+    frontier: Some("stack"),
+    head: Some(2),
+    open: Some(9),
+    visit: Some(5),
+    trail: None,
+    path: Some(4),
+};
+
+const DIJKSTRA: Listing = Listing {
+    file: "dijkstra.rs",
+    lines: &[
+        "dist[start] <- 0; heap <- [(0, start)]",
+        "while heap is not empty:",
+        "    (d, current) <- heap.pop_min()",
+        "    if d > dist[current]: continue",
+        "    if current = end: return path",
+        "    close current",
+        "    for each neighbour n of current:",
+        "        alt <- d + cost(n)",
+        "        if alt < dist[n]:",
+        "            dist[n] <- alt; parent[n] <- current",
+        "            heap.push((alt, n))",
+        "return no path",
+    ],
+    frontier: Some("heap"),
+    head: Some(2),
+    open: Some(10),
+    visit: Some(5),
+    trail: None,
+    path: Some(4),
+};
+
+const ASTAR: Listing = Listing {
+    file: "astar.rs",
+    lines: &[
+        "g[start] <- 0; open <- [(h(start), start)]",
+        "while open is not empty:",
+        "    current <- open.pop_min_f()",
+        "    if current = end: return path",
+        "    close current",
+        "    for each neighbour n of current:",
+        "        g' <- g[current] + cost(n)",
+        "        if g' < g[n]:",
+        "            g[n] <- g'; parent[n] <- current",
+        "            open.push((g' + h(n), n))",
+        "return no path",
+    ],
+    frontier: Some("open"),
+    head: Some(2),
+    open: Some(9),
+    visit: Some(4),
+    trail: None,
+    path: Some(3),
+};
+
+const HPA: Listing = Listing {
+    file: "hpa.rs",
+    lines: &[
+        "build clusters, entrances and edges (once)",
+        "link start and end to their entrances",
+        "A* over the entrance graph",
+        "refine each hop into cells",
+        "return path",
+    ],
+    frontier: None,
+    head: None,
+    open: None,
+    visit: None,
+    trail: None,
+    path: None,
+};
+
+const WALL_FOLLOWER: Listing = Listing {
+    file: "wall_follower.rs",
+    lines: &[
+        "current <- start; heading <- east",
+        "walk straight until the hand meets a wall",
+        "while current != end:",
+        "    if (current, heading) seen: stuck",
+        "    turn: hand side, ahead, other side, back",
+        "    current <- next cell",
+        "    mark current visited",
+        "    push it on the trail, or cut the loop",
+        "return trail",
+    ],
+    frontier: Some("trail"),
+    head: Some(5),
+    open: None,
+    visit: Some(6),
+    trail: Some(7),
+    path: Some(8),
+};
+
+const TREMAUX: Listing = Listing {
+    file: "tremaux.rs",
+    lines: &[
+        "current <- start",
+        "while current != end:",
+        "    if back at a known cell on a fresh passage:",
+        "        turn back",
+        "    else take the least marked open passage",
+        "    chalk both ends of the passage",
+        "    current <- next cell",
+        "    mark current visited",
+        "    push it on the trail, or cut the loop",
+        "return trail",
+    ],
+    frontier: Some("trail"),
+    head: Some(6),
+    open: None,
+    visit: Some(7),
+    trail: Some(8),
+    path: Some(9),
+};
+
+const DEAD_END_FILLING: Listing = Listing {
+    file: "dead_end_filling.rs",
+    lines: &[
+        "stack <- every dead end",
+        "while stack is not empty:",
+        "    current <- stack.pop()",
+        "    if start, end or no longer a dead end: skip",
+        "    fill current",
+        "    for each open neighbour n of current:",
+        "        if n became a dead end: stack.push(n)",
+        "route <- BFS through the open cells",
+        "return route",
+    ],
+    frontier: None,
+    head: Some(2),
+    open: None,
+    visit: Some(4),
+    trail: None,
+    path: Some(8),
+};
+
+pub fn listing(algo: &str) -> Option<&'static Listing> {
+    match algo {
+        "BFS"               => Some(&BFS),
+        "DFS"               => Some(&DFS),
+        "Dijkstra"          => Some(&DIJKSTRA),
+        "A*"                => Some(&ASTAR),
+        "HPA*"              => Some(&HPA),
+        "Wall L" | "Wall R" => Some(&WALL_FOLLOWER),
+        "Tremaux"           => Some(&TREMAUX),
+        "Dead-end"          => Some(&DEAD_END_FILLING),
+        _                   => None,
+    }
+}
@@ -0,0 +1,254 @@
+// src/server.rs
+use std::io::{BufRead, Write};
+use crate::api::{self, Command};
+use crate::grid::{COST_MUD, COST_NORMAL, COST_WATER};
+use crate::json::{self, Value};
+use crate::{link, AppState};
+
+/// Headless mode (`--server`): one JSON command per line on stdin, one JSON
+/// reply per line on stdout, no window. Commands go through the same
+/// `AppState` the GUI drives, so results match what the sidebar shows.
+///
+/// Every reply has `"ok"` and, on failure, `"error"`; a request's `"id"`
+/// is echoed back. See the README for the command list.
+pub fn run() {
+    let mut state = AppState::new();
+    let mut out = std::io::stdout().lock();
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() { continue; }
+        let (reply, quit) = handle(&mut state, &line);
+        if writeln!(out, "{reply}").and_then(|()| out.flush()).is_err() || quit { break; }
+    }
+}
+
+/// The reply to one request line, and whether it asked to stop.
+fn handle(state: &mut AppState, line: &str) -> (String, bool) {
+    let request = match json::parse(line) {
+        Ok(request) => request,
+        Err(message) => return (failure("", &format!("bad JSON: {message}")), false),
+    };
+    let id = request.get("id").map_or(String::new(), |id| format!("\"id\":{id},"));
+    let quit = request.get("cmd").and_then(Value::as_str) == Some("quit");
+    let reply = match respond(state, &request) {
+        Ok(fields) if fields.is_empty() => format!("{{{id}\"ok\":true}}"),
+        Ok(fields)                      => format!("{{{id}\"ok\":true,{fields}}}"),
+        Err(message)                    => failure(&id, &message),
+    };
+    (reply, quit)
+}
+
+fn failure(id: &str, message: &str) -> String {
+    format!("{{{id}\"ok\":false,\"error\":{}}}", json::quote(message))
+}
+
+/// Runs the command and returns the reply's extra fields, already written
+/// as `"key":value` pairs.
+fn respond(state: &mut AppState, request: &Value) -> Result<String, String> {
+    let cmd = request.get("cmd").and_then(Value::as_str).ok_or("missing \"cmd\"")?;
+    let text = |key: &str| request.get(key).and_then(Value::as_str).ok_or(format!("{cmd} needs a \"{key}\" string"));
+    let number = |key: &str| request.get(key).and_then(Value::as_usize).ok_or(format!("{cmd} needs a whole \"{key}\""));
+    let size = |state: &AppState| format!("\"width\":{},\"height\":{}", state.grid.width, state.grid.height);
+
+    match cmd {
+        "create" => {
+            api::apply(state, Command::Create(number("width")?, number("height")?))?;
+            Ok(size(state))
+        }
+        "load" => {
+            api::apply(state, Command::Load(text("map")?.to_string()))?;
+            Ok(size(state))
+        }
+        "walls" => {
+            let wall = request.get("wall").map_or(Some(true), Value::as_bool).ok_or("\"wall\" must be true or false")?;
+            api::apply(state, Command::Walls(cells(request)?, wall))?;
+            Ok(String::new())
+        }
+        "terrain" => {
+            let cost = match text("terrain")? {
+                "normal" => COST_NORMAL,
+                "mud"    => COST_MUD,
+                "water"  => COST_WATER,
+                other    => return Err(format!("unknown terrain '{other}', expected normal, mud or water")),
+            };
+            api::apply(state, Command::Terrain(cells(request)?, cost))?;
+            Ok(String::new())
+        }
+        "start" | "end" => {
+            let (row, col) = (number("row")?, number("col")?);
+            api::apply(state, if cmd == "start" { Command::SetStart(row, col) } else { Command::SetEnd(row, col) })?;
+            Ok(String::new())
+        }
+        "generate" => {
+            let generator = api::generator(text("generator")?)?;
+            let seed = match request.get("seed") {
+                None       => None,
+                Some(seed) => Some(seed.as_u64().ok_or("\"seed\" must be a whole number")?),
+            };
+            api::apply(state, Command::Generate(generator, seed))?;
+            Ok(format!("\"generator\":{},{}", json::quote(generator.name()), size(state)))
+        }
+        "run" => {
+            let algo = request.get("algo").map(|_| text("algo")).transpose()?;
+            if request.get("heuristic").is_some() {
+                state.astar.heuristic = api::heuristic(text("heuristic")?)?;
+            }
+            if let Some(weight) = request.get("weight") {
+                let Value::Number(weight) = weight else { return Err("\"weight\" must be a number".to_string()) };
+                state.astar.weight = api::weight(*weight as f32)?;
+            }
+            api::apply(state, Command::Run(algo.map(str::to_string)))?;
+            // Walking solvers always record; play them out to the final grid.
+            state.skip_playback();
+            Ok(format!("\"stats\":{}", state.stats.to_json(state.last_algo)))
+        }
+        "stats" => Ok(format!("\"stats\":{}", state.stats.to_json(state.last_algo))),
+        "dump"  => Ok(dump(state)),
+        "quit"  => Ok(String::new()),
+        other   => Err(format!("unknown command '{other}'")),
+    }
+}
+
+/// `"cells": [[row, col], ...]`.
+fn cells(request: &Value) -> Result<Vec<(usize, usize)>, String> {
+    let list = request.get("cells").and_then(Value::as_array).ok_or("needs \"cells\": [[row, col], ...]")?;
+    list.iter()
+        .map(|cell| match cell.as_array() {
+            Some([row, col]) => row.as_usize().zip(col.as_usize()),
+            _                => None,
+        }.ok_or(format!("bad cell {cell}, expected [row, col]")))
+        .collect()
+}
+
+/// The grid in `Grid::to_text` form and as a link code, plus the last
+/// path as [row, col] cells in order from start to end.
+fn dump(state: &AppState) -> String {
+    let grid = &state.grid;
+    // Edits since the run reset the stats but leave the search data behind.
+    let route = if state.stats.path_found { state.search.route.as_slice() } else { &[] };
+    let path: Vec<String> = route.iter()
+        .map(|idx| format!("[{},{}]", idx / grid.width, idx % grid.width))
+        .collect();
+    format!(
+        "\"width\":{},\"height\":{},\"grid\":{},\"link\":{},\"path\":[{}]",
+        grid.width,
+        grid.height,
+        json::quote(&grid.to_text()),
+        json::quote(&link::encode(grid, state.last_algo)),
+        path.join(","),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sends each line in turn and returns the parsed replies.
+    fn session(lines: &[&str]) -> Vec<Value> {
+        let mut state = AppState::new();
+        lines.iter().map(|line| json::parse(&handle(&mut state, line).0).expect("reply is JSON")).collect()
+    }
+
+    fn ok(reply: &Value) -> bool {
+        reply.get("ok").and_then(Value::as_bool).expect("reply has ok")
+    }
+
+    fn error(reply: &Value) -> &str {
+        assert!(!ok(reply), "expected a failure, got {reply}");
+        reply.get("error").and_then(Value::as_str).expect("failure has error")
+    }
+
+    #[test]
+    fn create_walls_run_and_dump() {
+        let replies = session(&[
+            r#"{"id":7,"cmd":"create","width":5,"height":3}"#,
+            r#"{"cmd":"start","row":0,"col":0}"#,
+            r#"{"cmd":"end","row":0,"col":4}"#,
+            r#"{"cmd":"walls","cells":[[0,2],[1,2]]}"#,
+            r#"{"cmd":"run","algo":"bfs"}"#,
+            r#"{"cmd":"dump"}"#,
+        ]);
+        assert!(replies.iter().all(ok));
+        assert_eq!(replies[0].get("id"), Some(&Value::Number(7.0)));
+        let stats = replies[4].get("stats").unwrap();
+        assert_eq!(stats.get("path_found").and_then(Value::as_bool), Some(true));
+        assert_eq!(stats.get("path_length").and_then(Value::as_usize), Some(8));
+
+        let dump = &replies[5];
+        assert_eq!(dump.get("grid").and_then(Value::as_str), Some("S.#.E\n..#..\n.....\n"));
+        let path: Vec<(usize, usize)> = dump.get("path").and_then(Value::as_array).unwrap().iter()
+            .map(|cell| match cell.as_array() {
+                Some([row, col]) => (row.as_usize().unwrap(), col.as_usize().unwrap()),
+                _                => panic!("bad cell {cell}"),
+            })
+            .collect();
+        assert_eq!((path.first(), path.last(), path.len()), (Some(&(0, 0)), Some(&(0, 4)), 9));
+        assert!(path.windows(2).all(|pair| pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1) == 1));
+    }
+
+    #[test]
+    fn load_accepts_text_and_links() {
+        let replies = session(&[r#"{"cmd":"load","map":"S..\n.#.\n..E"}"#, r#"{"cmd":"dump"}"#]);
+        assert!(ok(&replies[0]));
+        let link = replies[1].get("link").and_then(Value::as_str).unwrap().to_string();
+        let replies = session(&[&format!(r#"{{"cmd":"load","map":"{link}"}}"#), r#"{"cmd":"run","algo":"astar"}"#]);
+        assert!(replies.iter().all(ok));
+        assert_eq!(replies[0].get("width").and_then(Value::as_usize), Some(3));
+    }
+
+    #[test]
+    fn out_of_range_sizes_fail_without_panicking() {
+        for line in [
+            r#"{"cmd":"create","width":2,"height":40}"#,
+            r#"{"cmd":"create","width":60,"height":1001}"#,
+            r#"{"cmd":"create","width":-5,"height":10}"#,
+            r#"{"cmd":"load","map":"..."}"#,
+            r#"{"cmd":"load","map":"S.\n.E"}"#,
+            r#"{"cmd":"load","map":"not a map"}"#,
+        ] {
+            let replies = session(&[line, r#"{"cmd":"run","algo":"bfs"}"#]);
+            error(&replies[0]);
+            assert!(ok(&replies[1]), "the old grid still runs after {line}");
+        }
+    }
+
+    #[test]
+    fn out_of_range_cells_fail_and_change_nothing() {
+        let replies = session(&[
+            r#"{"cmd":"create","width":4,"height":4}"#,
+            r#"{"cmd":"walls","cells":[[1,1],[4,0]]}"#,
+            r#"{"cmd":"terrain","terrain":"mud","cells":[[0,9]]}"#,
+            r#"{"cmd":"start","row":99,"col":0}"#,
+            r#"{"cmd":"walls","cells":[[1]]}"#,
+            r#"{"cmd":"dump"}"#,
+        ]);
+        assert!(error(&replies[1]).contains("outside the 4x4 grid"));
+        error(&replies[2]);
+        error(&replies[3]);
+        error(&replies[4]);
+        assert_eq!(replies[5].get("grid").and_then(Value::as_str), Some("....\n.S..\n..E.\n....\n"));
+    }
+
+    #[test]
+    fn bad_requests_fail() {
+        let replies = session(&[
+            "{not json",
+            r#"{"id":"a"}"#,
+            r#"{"cmd":"fly"}"#,
+            r#"{"cmd":"run","algo":"teleport"}"#,
+            r#"{"cmd":"run","algo":"astar","weight":99}"#,
+            r#"{"cmd":"generate","generator":"wavecollapse"}"#,
+        ]);
+        assert!(error(&replies[0]).starts_with("bad JSON"));
+        assert_eq!(replies[1].get("id").and_then(Value::as_str), Some("a"));
+        assert!(error(&replies[1]).contains("cmd"));
+        for reply in &replies[2..] { error(reply); }
+    }
+
+    #[test]
+    fn quit_stops_the_loop() {
+        let mut state = AppState::new();
+        assert!(handle(&mut state, r#"{"cmd":"quit"}"#).1);
+        assert!(!handle(&mut state, r#"{"cmd":"stats"}"#).1);
+    }
+}
@@ -0,0 +1,143 @@
+// src/session.rs
+use crate::grid::Grid;
+use crate::{algorithms, api, fits, AppState, Tool, SPEEDS};
+
+const SETTINGS_KEY: &str = "settings";
+const GRID_KEY: &str = "grid";
+
+/// Seconds between autosave checks.
+const AUTOSAVE_INTERVAL: f64 = 2.0;
+
+/// Settings as `key = value` lines, in the same style as theme files.
+pub fn settings_text(state: &AppState) -> String {
+    format!(
+        "theme = {}\nwidth = {}\nheight = {}\nspeed = {}\nalgorithm = {}\ntool = {}\nanimate = {}\nheuristic = {}\nweight = {}\n",
+        state.theme().name,
+        state.grid.width,
+        state.grid.height,
+        state.speed,
+        state.last_algo.unwrap_or(""),
+        state.active_tool.name(),
+        state.animate_runs,
+        state.astar.heuristic.name(),
+        state.astar.weight,
+    )
+}
+
+/// Puts back the last session's settings and grid. Unknown keys and bad
+/// values are skipped so an old or hand-edited file still loads.
+pub fn restore(state: &mut AppState) {
+    let mut algo = None;
+    if let Some(text) = store::read(SETTINGS_KEY) {
+        let (mut width, mut height) = (state.grid.width, state.grid.height);
+        for (key, value) in text.lines().filter_map(|line| line.split_once('=')) {
+            let value = value.trim();
+            match key.trim() {
+                "theme"     => if let Some(i) = state.themes.iter().position(|t| t.name == value) { state.theme = i },
+                "width"     => width = value.parse().unwrap_or(width),
+                "height"    => height = value.parse().unwrap_or(height),
+                "speed"     => if let Ok(speed) = value.parse::<usize>() { state.speed = speed.min(SPEEDS.len() - 1) },
+                "algorithm" => algo = algorithms::NAMES.into_iter().find(|&name| name == value),
+                "tool"      => if let Some(&tool) = Tool::ALL.iter().find(|t| t.name() == value) { state.active_tool = tool },
+                "animate"   => state.animate_runs = value == "true",
+                "heuristic" => if let Ok(heuristic) = api::heuristic(value) { state.astar.heuristic = heuristic },
+                "weight"    => if let Some(weight) = value.parse().ok().and_then(|w| api::weight(w).ok()) { state.astar.weight = weight },
+                _           => {}
+            }
+        }
+        if fits(width, height) && (width, height) != (state.grid.width, state.grid.height) {
+            state.resize(width, height);
+        }
+    }
+
+    // Sizes the app would not make are ignored, so a corrupt or hand-edited
+    // save cannot ask for a huge grid. Measure before `from_text` allocates.
+    let grid = store::read(GRID_KEY).filter(|text| {
+        let mut rows = text.lines().map(str::trim_end).filter(|row| !row.is_empty());
+        let width = rows.next().map_or(0, |row| row.chars().count());
+        fits(width, rows.count() + 1)
+    });
+    if let Some(grid) = grid.as_deref().and_then(Grid::from_text) {
+        state.load_grid(grid, None).ok();
+    }
+    // Rerun the last search so the grid looks as it was left.
+    if let Some(algo) = algo {
+        state.run_algo(algo);
+        state.skip_playback();
+    }
+}
+
+/// Writes settings and grid every couple of seconds, and only what changed.
+#[derive(Default)]
+pub struct Autosave {
+    last_check: f64,
+    settings: String,
+    grid: String,
+}
+
+impl Autosave {
+    pub fn tick(&mut self, state: &AppState, now: f64) {
+        // Mid-replay the grid is only partly drawn; wait until it is done.
+        if now - self.last_check < AUTOSAVE_INTERVAL || state.maze_playback.is_some() { return; }
+        self.last_check = now;
+
+        let settings = settings_text(state);
+        if settings != self.settings {
+            store::write(SETTINGS_KEY, &settings);
+            self.settings = settings;
+        }
+        let grid = state.grid.to_text();
+        if grid != self.grid {
+            store::write(GRID_KEY, &grid);
+            self.grid = grid;
+        }
+    }
+}
+
+/// Files in the user's config directory.
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+mod store {
+    use std::path::PathBuf;
+
+    fn dir() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()).map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("rust-wasm-algo-lab"))
+    }
+
+    pub fn read(key: &str) -> Option<String> {
+        std::fs::read_to_string(dir()?.join(format!("{key}.txt"))).ok()
+    }
+
+    /// Best effort: a read-only or missing config directory just means
+    /// nothing is remembered.
+    pub fn write(key: &str, value: &str) {
+        let Some(dir) = dir() else { return };
+        if std::fs::create_dir_all(&dir).is_ok() {
+            let _ = std::fs::write(dir.join(format!("{key}.txt")), value);
+        }
+    }
+}
+
+/// The browser's `localStorage`.
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+mod store {
+    use js_sys::wasm_bindgen::JsValue;
+    use crate::web;
+
+    const PREFIX: &str = "rust-wasm-algo-lab/";
+
+    pub fn read(key: &str) -> Option<String> {
+        let storage = web::global("localStorage")?;
+        let key = JsValue::from_str(&format!("{PREFIX}{key}"));
+        web::call(&storage, "getItem", &[key])?.as_string()
+    }
+
+    /// Best effort: storage can be full or disabled.
+    pub fn write(key: &str, value: &str) {
+        let Some(storage) = web::global("localStorage") else { return };
+        let key = JsValue::from_str(&format!("{PREFIX}{key}"));
+        web::call(&storage, "setItem", &[key, JsValue::from_str(value)]);
+    }
+}
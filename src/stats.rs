@@ -1,4 +1,5 @@
 // src/stats.rs
+use crate::grid::{Grid, COST_MUD, COST_WATER};
 
 #[derive(Default, Clone, Copy)]
 pub struct Stats {
@@ -6,4 +7,53 @@ pub struct Stats {
     pub path_length: u32,
     pub execution_ms: f64,
     pub path_found: bool,
-}
\ No newline at end of file
+    pub preprocess_ms: f64,
+    /// Moves made by a walking solver (wall follower, Tremaux...).
+    pub steps: u32,
+    /// The wall follower came back to a spot it had already left the same way.
+    pub looped: bool,
+    /// Sum of the costs of the cells entered along the path.
+    pub path_cost: u32,
+    /// Path steps onto normal ground, mud and water.
+    pub terrain_steps: [u32; 3],
+    /// Largest the open set (queue, stack or heap) grew.
+    pub max_open: u32,
+    /// Peak bytes held in the search's working arrays and open set.
+    pub peak_bytes: usize,
+}
+
+impl Stats {
+    /// Fills in cost and terrain mix from the path marked on `grid`.
+    pub fn measure_path(&mut self, grid: &Grid) {
+        if !self.path_found { return; }
+        for i in 0..(grid.width * grid.height) {
+            let cell = grid.cell(i);
+            if !cell.is_path && !cell.is_end { continue; }
+            self.path_cost += cell.cost as u32;
+            let terrain = match cell.cost {
+                COST_MUD   => 1,
+                COST_WATER => 2,
+                _          => 0,
+            };
+            self.terrain_steps[terrain] += 1;
+        }
+    }
+
+    /// One JSON object for hosts driving the app from outside. `algo` is
+    /// the solver that produced these numbers, or `null` when there is none.
+    pub fn to_json(self, algo: Option<&str>) -> String {
+        let algo = algo.map_or("null".to_string(), |name| format!("\"{name}\""));
+        format!(
+            concat!(
+                "{{\"algorithm\":{},\"path_found\":{},\"nodes_explored\":{},\"path_length\":{},",
+                "\"path_cost\":{},\"terrain_steps\":{{\"normal\":{},\"mud\":{},\"water\":{}}},",
+                "\"steps\":{},\"looped\":{},\"max_open\":{},\"peak_bytes\":{},",
+                "\"execution_ms\":{},\"preprocess_ms\":{}}}",
+            ),
+            algo, self.path_found, self.nodes_explored, self.path_length,
+            self.path_cost, self.terrain_steps[0], self.terrain_steps[1], self.terrain_steps[2],
+            self.steps, self.looped, self.max_open, self.peak_bytes,
+            self.execution_ms, self.preprocess_ms,
+        )
+    }
+}
@@ -0,0 +1,394 @@
+// src/theme.rs
+use macroquad::prelude::*;
+
+/// Every colour the app draws with: the sidebar palette first, then what
+/// each kind of cell looks like on the grid.
+#[derive(Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub bg: Color,
+    pub surface: Color,
+    pub overlay: Color,
+    pub text: Color,
+    pub subtext: Color,
+    pub accent: Color,
+    pub red: Color,
+    pub green: Color,
+    pub yellow: Color,
+    pub mauve: Color,
+    pub peach: Color,
+
+    pub floor: Color,
+    pub wall: Color,
+    pub grid_line: Color,
+    pub start: Color,
+    pub end: Color,
+    /// In the open set.
+    pub open: Color,
+    /// Expanded (the closed set).
+    pub visited: Color,
+    /// The cell a replay is working on.
+    pub current: Color,
+    pub path: Color,
+    /// A walker's trail or a generator's working set, drawn translucent.
+    pub trail: Color,
+    pub mud: Color,
+    pub water: Color,
+    pub arrow: Color,
+    /// Heatmap stops, cold to hot.
+    pub ramp: [Color; 6],
+}
+
+impl Theme {
+    /// Catppuccin Mocha.
+    pub fn dark() -> Self {
+        Self {
+            name: "Dark".to_string(),
+            bg:        color_u8!(24, 24, 37, 255),
+            surface:   color_u8!(30, 30, 46, 255),
+            overlay:   color_u8!(49, 50, 68, 255),
+            text:      color_u8!(205, 214, 244, 255),
+            subtext:   color_u8!(166, 173, 200, 255),
+            accent:    color_u8!(137, 180, 250, 255),
+            red:       color_u8!(243, 139, 168, 255),
+            green:     color_u8!(166, 227, 161, 255),
+            yellow:    color_u8!(249, 226, 175, 255),
+            mauve:     color_u8!(203, 166, 247, 255),
+            peach:     color_u8!(250, 179, 135, 255),
+            floor:     color_u8!(30, 30, 46, 255),
+            wall:      color_u8!(69, 71, 90, 255),
+            grid_line: color_u8!(0, 0, 0, 20),
+            start:     color_u8!(166, 227, 161, 255),
+            end:       color_u8!(243, 139, 168, 255),
+            open:      color_u8!(148, 226, 213, 255),
+            visited:   color_u8!(137, 180, 250, 255),
+            current:   color_u8!(250, 179, 135, 255),
+            path:      color_u8!(249, 226, 175, 255),
+            trail:     color_u8!(203, 166, 247, 140),
+            mud:       color_u8!(161, 138, 90, 255),
+            water:     color_u8!(90, 138, 161, 255),
+            arrow:     color_u8!(17, 17, 27, 200),
+            ramp: [
+                color_u8!(137, 180, 250, 255),
+                color_u8!(148, 226, 213, 255),
+                color_u8!(166, 227, 161, 255),
+                color_u8!(249, 226, 175, 255),
+                color_u8!(250, 179, 135, 255),
+                color_u8!(243, 139, 168, 255),
+            ],
+        }
+    }
+
+    /// Catppuccin Latte.
+    pub fn light() -> Self {
+        Self {
+            name: "Light".to_string(),
+            bg:        color_u8!(230, 233, 239, 255),
+            surface:   color_u8!(239, 241, 245, 255),
+            overlay:   color_u8!(204, 208, 218, 255),
+            text:      color_u8!(76, 79, 105, 255),
+            subtext:   color_u8!(108, 111, 133, 255),
+            accent:    color_u8!(30, 102, 245, 255),
+            red:       color_u8!(210, 15, 57, 255),
+            green:     color_u8!(64, 160, 43, 255),
+            yellow:    color_u8!(223, 142, 29, 255),
+            mauve:     color_u8!(136, 57, 239, 255),
+            peach:     color_u8!(254, 100, 11, 255),
+            floor:     color_u8!(239, 241, 245, 255),
+            wall:      color_u8!(124, 127, 147, 255),
+            grid_line: color_u8!(0, 0, 0, 25),
+            start:     color_u8!(64, 160, 43, 255),
+            end:       color_u8!(210, 15, 57, 255),
+            open:      color_u8!(153, 222, 214, 255),
+            visited:   color_u8!(167, 196, 252, 255),
+            current:   color_u8!(254, 100, 11, 255),
+            path:      color_u8!(223, 142, 29, 255),
+            trail:     color_u8!(136, 57, 239, 110),
+            mud:       color_u8!(176, 136, 88, 255),
+            water:     color_u8!(70, 130, 180, 255),
+            arrow:     color_u8!(76, 79, 105, 200),
+            ramp: [
+                color_u8!(30, 102, 245, 255),
+                color_u8!(23, 146, 153, 255),
+                color_u8!(64, 160, 43, 255),
+                color_u8!(223, 142, 29, 255),
+                color_u8!(254, 100, 11, 255),
+                color_u8!(210, 15, 57, 255),
+            ],
+        }
+    }
+
+    /// White walls on black, saturated states told apart by brightness too.
+    pub fn high_contrast() -> Self {
+        Self {
+            name: "High contrast".to_string(),
+            bg:        color_u8!(0, 0, 0, 255),
+            surface:   color_u8!(20, 20, 20, 255),
+            overlay:   color_u8!(110, 110, 110, 255),
+            text:      color_u8!(255, 255, 255, 255),
+            subtext:   color_u8!(210, 210, 210, 255),
+            accent:    color_u8!(0, 200, 255, 255),
+            red:       color_u8!(255, 60, 60, 255),
+            green:     color_u8!(0, 255, 0, 255),
+            yellow:    color_u8!(255, 255, 0, 255),
+            mauve:     color_u8!(255, 0, 255, 255),
+            peach:     color_u8!(255, 140, 0, 255),
+            floor:     color_u8!(0, 0, 0, 255),
+            wall:      color_u8!(255, 255, 255, 255),
+            grid_line: color_u8!(255, 255, 255, 30),
+            start:     color_u8!(0, 255, 0, 255),
+            end:       color_u8!(255, 0, 0, 255),
+            open:      color_u8!(0, 140, 140, 255),
+            visited:   color_u8!(60, 60, 170, 255),
+            current:   color_u8!(255, 140, 0, 255),
+            path:      color_u8!(255, 255, 0, 255),
+            trail:     color_u8!(255, 0, 255, 150),
+            mud:       color_u8!(150, 90, 20, 255),
+            water:     color_u8!(0, 170, 255, 255),
+            arrow:     color_u8!(255, 255, 255, 200),
+            ramp: [
+                color_u8!(0, 0, 255, 255),
+                color_u8!(0, 255, 255, 255),
+                color_u8!(0, 255, 0, 255),
+                color_u8!(255, 255, 0, 255),
+                color_u8!(255, 140, 0, 255),
+                color_u8!(255, 0, 0, 255),
+            ],
+        }
+    }
+
+    /// Okabe-Ito colours, which stay apart under red-green and blue-yellow
+    /// colour blindness, on the dark sidebar. The heatmap uses viridis.
+    pub fn colour_blind() -> Self {
+        Self {
+            name: "Colour-blind".to_string(),
+            accent:    color_u8!(86, 180, 233, 255),
+            red:       color_u8!(213, 94, 0, 255),
+            green:     color_u8!(0, 158, 115, 255),
+            yellow:    color_u8!(240, 228, 66, 255),
+            mauve:     color_u8!(204, 121, 167, 255),
+            peach:     color_u8!(230, 159, 0, 255),
+            wall:      color_u8!(100, 100, 110, 255),
+            start:     color_u8!(245, 245, 245, 255),
+            end:       color_u8!(213, 94, 0, 255),
+            open:      color_u8!(0, 158, 115, 255),
+            visited:   color_u8!(0, 114, 178, 255),
+            current:   color_u8!(230, 159, 0, 255),
+            path:      color_u8!(240, 228, 66, 255),
+            trail:     color_u8!(245, 245, 245, 120),
+            mud:       color_u8!(204, 121, 167, 255),
+            water:     color_u8!(86, 180, 233, 255),
+            ramp: VIRIDIS,
+            ..Self::dark()
+        }
+    }
+
+    /// The same cell colours on the light sidebar.
+    pub fn colour_blind_light() -> Self {
+        let light = Self::light();
+        Self {
+            name: "Colour-blind light".to_string(),
+            accent:    color_u8!(0, 114, 178, 255),
+            wall:      color_u8!(90, 90, 100, 255),
+            start:     color_u8!(0, 0, 0, 255),
+            trail:     color_u8!(0, 0, 0, 90),
+            bg:        light.bg,
+            surface:   light.surface,
+            overlay:   light.overlay,
+            text:      light.text,
+            subtext:   light.subtext,
+            floor:     light.floor,
+            grid_line: light.grid_line,
+            arrow:     light.arrow,
+            ..Self::colour_blind()
+        }
+    }
+
+    /// Reads a theme file: `key = #rrggbb` (or `#rrggbbaa`) per line, with
+    /// `name = ...` and an optional `base = ...` naming a built-in to start
+    /// from. Keys are the field names; anything not given comes from the
+    /// base, wherever in the file it is named. `;` starts a comment.
+    pub fn parse(text: &str) -> Result<Theme, String> {
+        let mut lines = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split(';').next().unwrap_or("").trim();
+            if line.is_empty() { continue; }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected key = value", number + 1));
+            };
+            lines.push((number + 1, key.trim(), value.trim()));
+        }
+
+        let mut theme = Theme::dark();
+        let mut bases = lines.iter().filter(|(_, key, _)| *key == "base");
+        if let Some(&(number, _, value)) = bases.next() {
+            if let Some((again, ..)) = bases.next() {
+                return Err(format!("line {again}: base is already given on line {number}"));
+            }
+            theme = builtin().into_iter()
+                .find(|t| t.name.eq_ignore_ascii_case(value))
+                .ok_or(format!("line {number}: no built-in theme '{value}'"))?;
+        }
+
+        let mut name = None;
+        for (number, key, value) in lines {
+            match key {
+                "name" => name = Some(value.to_string()),
+                "base" => {}
+                "ramp" => {
+                    let stops: Vec<Color> = value.split_whitespace().map(parse_color).collect::<Option<_>>()
+                        .ok_or(format!("line {number}: bad colour in ramp"))?;
+                    theme.ramp = stops.try_into()
+                        .map_err(|_| format!("line {number}: ramp needs 6 colours"))?;
+                }
+                _ => {
+                    let slot = theme.slot(key).ok_or(format!("line {number}: unknown key '{key}'"))?;
+                    *slot = parse_color(value).ok_or(format!("line {number}: bad colour '{value}'"))?;
+                }
+            }
+        }
+        theme.name = name.ok_or("missing name".to_string())?;
+        Ok(theme)
+    }
+
+    fn slot(&mut self, key: &str) -> Option<&mut Color> {
+        Some(match key {
+            "bg"        => &mut self.bg,
+            "surface"   => &mut self.surface,
+            "overlay"   => &mut self.overlay,
+            "text"      => &mut self.text,
+            "subtext"   => &mut self.subtext,
+            "accent"    => &mut self.accent,
+            "red"       => &mut self.red,
+            "green"     => &mut self.green,
+            "yellow"    => &mut self.yellow,
+            "mauve"     => &mut self.mauve,
+            "peach"     => &mut self.peach,
+            "floor"     => &mut self.floor,
+            "wall"      => &mut self.wall,
+            "grid_line" => &mut self.grid_line,
+            "start"     => &mut self.start,
+            "end"       => &mut self.end,
+            "open"      => &mut self.open,
+            "visited"   => &mut self.visited,
+            "current"   => &mut self.current,
+            "path"      => &mut self.path,
+            "trail"     => &mut self.trail,
+            "mud"       => &mut self.mud,
+            "water"     => &mut self.water,
+            "arrow"     => &mut self.arrow,
+            _           => return None,
+        })
+    }
+}
+
+const VIRIDIS: [Color; 6] = [
+    color_u8!(68, 1, 84, 255),
+    color_u8!(65, 68, 135, 255),
+    color_u8!(42, 120, 142, 255),
+    color_u8!(34, 168, 132, 255),
+    color_u8!(122, 209, 81, 255),
+    color_u8!(253, 231, 37, 255),
+];
+
+pub fn builtin() -> Vec<Theme> {
+    vec![
+        Theme::dark(),
+        Theme::light(),
+        Theme::high_contrast(),
+        Theme::colour_blind(),
+        Theme::colour_blind_light(),
+    ]
+}
+
+/// Built-in themes followed by every `*.theme` file in `dir`, and a line
+/// saying what was loaded or why a file was skipped.
+pub fn load(dir: &str) -> (Vec<Theme>, String) {
+    let mut themes = builtin();
+    let Ok(entries) = std::fs::read_dir(dir) else { return (themes, String::new()) };
+    let mut paths: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "theme"))
+        .collect();
+    paths.sort();
+
+    let (mut loaded, mut error) = (0, None);
+    for path in paths {
+        let file = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        match std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| Theme::parse(&text)) {
+            Ok(theme) => {
+                themes.retain(|t| t.name != theme.name);
+                themes.push(theme);
+                loaded += 1;
+            }
+            Err(e) => error = Some(format!("{file}: {e}")),
+        }
+    }
+    let report = error.unwrap_or_else(|| format!("{loaded} theme file(s) loaded"));
+    (themes, report)
+}
+
+fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) { return None; }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { byte(6)? } else { 255 };
+    Some(Color::from_rgba(byte(0)?, byte(2)?, byte(4)?, alpha))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solarized_example_loads() {
+        let text = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/themes/solarized.theme")).unwrap();
+        let theme = Theme::parse(&text).unwrap();
+        assert_eq!(theme.name, "Solarized");
+        assert_eq!(theme.bg, color_u8!(0x00, 0x2b, 0x36, 255));
+        assert_eq!(theme.trail, color_u8!(0x6c, 0x71, 0xc4, 0x8c));
+        assert_eq!(theme.ramp[5], color_u8!(0xdc, 0x32, 0x2f, 255));
+        // Not in the file, so taken from the dark base.
+        assert_eq!(theme.grid_line, Theme::dark().grid_line);
+        assert_eq!(theme.arrow, Theme::dark().arrow);
+    }
+
+    #[test]
+    fn base_applies_wherever_it_is_named() {
+        let first = Theme::parse("name = Mine\nbase = light\nwall = #102030").unwrap();
+        let last = Theme::parse("name = Mine\nwall = #102030\nbase = Light").unwrap();
+        for theme in [first, last] {
+            assert_eq!(theme.name, "Mine");
+            assert_eq!(theme.wall, color_u8!(0x10, 0x20, 0x30, 255));
+            assert_eq!(theme.floor, Theme::light().floor);
+        }
+        assert_eq!(Theme::parse("name = Plain").unwrap().floor, Theme::dark().floor);
+    }
+
+    #[test]
+    fn reports_the_bad_line() {
+        let cases = [
+            ("wall = #000000", "missing name"),
+            ("name = X\nwall #000000", "line 2: expected key = value"),
+            ("name = X\n\n; comment\nwalls = #000000", "line 4: unknown key 'walls'"),
+            ("name = X\nwall = 000000", "line 2: bad colour '000000'"),
+            ("name = X\nwall = #00000", "line 2: bad colour '#00000'"),
+            ("name = X\nwall = #gg0000", "line 2: bad colour '#gg0000'"),
+            ("name = X\nramp = #000000 #ffffff", "line 2: ramp needs 6 colours"),
+            ("name = X\nramp = #000000 white", "line 2: bad colour in ramp"),
+            ("name = X\nbase = neon", "line 2: no built-in theme 'neon'"),
+            ("base = dark\nname = X\nbase = light", "line 3: base is already given on line 1"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(Theme::parse(text).err().as_deref(), Some(expected), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn comments_and_alpha_are_read() {
+        let theme = Theme::parse("name = X ; trailing\nwall = #ff000080 ; half\n").unwrap();
+        assert_eq!(theme.name, "X");
+        assert_eq!(theme.wall, color_u8!(255, 0, 0, 0x80));
+    }
+}
@@ -1,131 +1,725 @@
 // src/ui.rs
 use macroquad::prelude::*;
-use crate::{AppState, Tool, SIDEBAR_WIDTH, GRID_WIDTH, GRID_HEIGHT};
+use crate::{algorithms, analysis, compare, AppState, Tool, SIDEBAR_WIDTH, SPEEDS};
+use crate::maze::Generator;
+use crate::maze::wfc::Sample;
+use crate::maze::braid::LEVELS;
+use crate::maze::mask::Scope;
+use crate::grid::{COST_MUD, COST_WATER};
+use crate::heatmap::{self, Heatmap};
+use crate::brush::{Shape, MAX_RADIUS};
+use crate::console::{self, Console};
+use crate::pseudocode;
+use crate::theme::Theme;
 
 const BTN_H: f32 = 30.0;
 const BTN_MARGIN: f32 = 6.0;
 const SECTION_MARGIN: f32 = 16.0;
 const TEXT_SIZE: f32 = 14.0;
-
-// Catppuccin Mocha
-const BG:       Color = color_u8!(24, 24, 37, 255);
-const SURFACE:  Color = color_u8!(30, 30, 46, 255);
-const OVERLAY:  Color = color_u8!(49, 50, 68, 255);
-const TEXT:     Color = color_u8!(205, 214, 244, 255);
-const SUBTEXT:  Color = color_u8!(166, 173, 200, 255);
-const ACCENT:   Color = color_u8!(137, 180, 250, 255);
-const RED:      Color = color_u8!(243, 139, 168, 255);
-const GREEN:    Color = color_u8!(166, 227, 161, 255);
-const YELLOW:   Color = color_u8!(249, 226, 175, 255);
+const SCROLL_STEP: f32 = 40.0;
 
 pub struct Ui {
+    /// Cell of the main grid under the mouse, set each frame.
     pub hovered: Option<usize>,
+    scroll: f32,
+    /// The app's theme, refreshed at the start of every frame.
+    theme: Theme,
 }
 
 impl Ui {
     pub fn new() -> Self {
-        Self { hovered: None }
+        Self { hovered: None, scroll: 0.0, theme: Theme::dark() }
     }
 
     pub fn draw(&mut self, state: &mut AppState, grid_w: f32, _screen_w: f32, screen_h: f32) {
+        self.theme = state.theme().clone();
+
         // Sidebar background
-        draw_rectangle(grid_w, 0.0, SIDEBAR_WIDTH, screen_h, BG);
-        draw_line(grid_w, 0.0, grid_w, screen_h, 1.0, OVERLAY);
+        draw_rectangle(grid_w, 0.0, SIDEBAR_WIDTH, screen_h, self.theme.bg);
+        draw_line(grid_w, 0.0, grid_w, screen_h, 1.0, self.theme.overlay);
+
+        // The sidebar outgrows short windows, so it scrolls with the wheel.
+        let (mx, _) = mouse_position();
+        if mx >= grid_w {
+            let (_, wheel) = mouse_wheel();
+            self.scroll -= wheel.signum() * SCROLL_STEP;
+        }
 
         let x = grid_w + 12.0;
         let w = SIDEBAR_WIDTH - 24.0;
-        let mut y = 16.0;
+        let mut y = 16.0 - self.scroll;
 
         // Title
-        draw_text("Algorithm Lab", x, y + TEXT_SIZE, TEXT_SIZE + 2.0, ACCENT);
+        draw_text("Algorithm Lab", x, y + TEXT_SIZE, TEXT_SIZE + 2.0, self.theme.accent);
         y += TEXT_SIZE + SECTION_MARGIN + 4.0;
 
         // Algorithms
         y = self.section_label("ALGORITHMS", x, y);
-        for name in ["BFS", "DFS", "Dijkstra", "A*"] {
+        let half_w = (w - BTN_MARGIN) / 2.0;
+        let names = algorithms::NAMES;
+        for (i, name) in names.into_iter().enumerate() {
+            let bx = x + (i % 2) as f32 * (half_w + BTN_MARGIN);
+            let by = y + (i / 2) as f32 * (BTN_H + BTN_MARGIN);
             let active = state.last_algo == Some(name);
-            if self.button(name, x, y, w, active, ACCENT) {
+            if self.button(name, bx, by, half_w, active, self.theme.accent) {
                 state.run_algo(name);
             }
-            y += BTN_H + BTN_MARGIN;
         }
+        y += names.len().div_ceil(2) as f32 * (BTN_H + BTN_MARGIN);
+        let label = if state.animate_runs { "Animate: on" } else { "Animate: off" };
+        if self.button(label, x, y, half_w, state.animate_runs, self.theme.accent) {
+            state.animate_runs = !state.animate_runs;
+        }
+        let label = match state.panes.len() {
+            0 => "Split: off".to_string(),
+            n => format!("Split: {n}"),
+        };
+        if self.button(&label, x + half_w + BTN_MARGIN, y, half_w, !state.panes.is_empty(), self.theme.accent) {
+            state.set_panes(match state.panes.len() {
+                0 => 2,
+                n if n < compare::MAX_PANES => n + 1,
+                _ => 0,
+            });
+        }
+        y += BTN_H + BTN_MARGIN;
+        let heat = state.heatmap;
+        if self.button(&format!("Heatmap: {}", heat.name()), x, y, w, heat != Heatmap::Off, self.theme.accent) {
+            let all = Heatmap::ALL;
+            let current = all.iter().position(|&h| h == heat).unwrap_or(0);
+            state.heatmap = all[(current + 1) % all.len()];
+        }
+        y += BTN_H + BTN_MARGIN;
+        if heat != Heatmap::Off {
+            y = self.legend(state, x, y, w);
+        }
+        let label = if state.show_parents { "Arrows: on" } else { "Arrows: off" };
+        if self.button(label, x, y, half_w, state.show_parents, self.theme.accent) {
+            state.show_parents = !state.show_parents;
+        }
+        let label = if state.show_code { "Code: on" } else { "Code: off" };
+        if self.button(label, x + half_w + BTN_MARGIN, y, half_w, state.show_code, self.theme.accent) {
+            state.show_code = !state.show_code;
+        }
+        y += BTN_H + BTN_MARGIN;
+        // Key for the search colours on the grid.
+        let mut kx = x;
+        for (name, color) in [("open", self.theme.open), ("closed", self.theme.visited), ("current", self.theme.current), ("path", self.theme.path)] {
+            draw_rectangle(kx, y + 3.0, 8.0, 8.0, color);
+            draw_text(name, kx + 11.0, y + 11.0, TEXT_SIZE - 2.0, self.theme.subtext);
+            kx += 14.0 + measure_text(name, None, (TEXT_SIZE - 2.0) as u16, 1.0).width + 6.0;
+        }
+        y += TEXT_SIZE + BTN_MARGIN;
         y += SECTION_MARGIN;
 
+        if !state.panes.is_empty() {
+            y = self.compare_table(state, x, y, w);
+        }
+
         // Maze
         y = self.section_label("MAZE", x, y);
-        if self.button("Recursive Backtracker", x, y, w, false, ACCENT) {
-        state.grid.reset_all();
-        maze::recursive_backtracker::generate(&mut state.grid);
+        let step = self.selector(state.maze_generator.name(), x, y, w);
+        if step != 0 {
+            let all = Generator::ALL;
+            let current = all.iter().position(|&g| g == state.maze_generator).unwrap_or(0);
+            state.maze_generator = all[(current as i32 + step).rem_euclid(all.len() as i32) as usize];
+        }
+        y += BTN_H + BTN_MARGIN;
+        if self.button("Generate", x, y, half_w, false, self.theme.accent) {
+            state.generate_maze(false);
+        }
+        let animating = state.maze_playback.is_some();
+        if self.button("Animate", x + half_w + BTN_MARGIN, y, half_w, animating, self.theme.accent) {
+            state.generate_maze(true);
         }
         y += BTN_H + BTN_MARGIN;
-        if self.button("Prim's", x, y, w, false, ACCENT) {
-        maze::prims::generate(&mut state.grid);
-        state.grid.set_start(state.grid.idx(1, 1));
-        state.grid.set_end(state.grid.idx(GRID_HEIGHT - 2, GRID_WIDTH - 2));
+        let post = &mut state.maze_post;
+        if self.button(&format!("Braid {}%", percent(post.dead_ends)), x, y, half_w, post.dead_ends > 0.0, self.theme.accent) {
+            post.dead_ends = next_level(post.dead_ends);
         }
-        y += BTN_H + BTN_MARGIN + SECTION_MARGIN;
+        if self.button(&format!("Loops {}%", percent(post.walls)), x + half_w + BTN_MARGIN, y, half_w, post.walls > 0.0, self.theme.accent) {
+            post.walls = next_level(post.walls);
+        }
+        y += BTN_H + BTN_MARGIN;
+        if self.button(&format!("Terrain {}%", percent(post.terrain)), x, y, half_w, post.terrain > 0.0, self.theme.accent) {
+            post.terrain = next_level(post.terrain);
+        }
+        if self.button(&format!("Speed {}/f", SPEEDS[state.speed]), x + half_w + BTN_MARGIN, y, half_w, false, self.theme.accent) {
+            state.speed = (state.speed + 1) % SPEEDS.len();
+        }
+        y += BTN_H + BTN_MARGIN;
+        let scope = state.maze_scope;
+        if self.button(&format!("In: {}", scope.name()), x, y, half_w, scope != Scope::All, self.theme.mauve) {
+            let all = Scope::ALL;
+            let current = all.iter().position(|&s| s == scope).unwrap_or(0);
+            state.maze_scope = all[(current + 1) % all.len()];
+        }
+        if self.button("Clear Mask", x + half_w + BTN_MARGIN, y, half_w, false, self.theme.overlay) {
+            state.mask.iter_mut().for_each(|inside| *inside = false);
+        }
+        y += BTN_H + BTN_MARGIN;
+        match state.maze_generator {
+            Generator::Cave    => y = self.cave_options(state, x, y, half_w),
+            Generator::Terrain => y = self.noise_options(state, x, y, half_w),
+            Generator::Dungeon => y = self.bsp_options(state, x, y, half_w),
+            Generator::WaveCollapse => y = self.wfc_options(state, x, y, half_w),
+            _ => {}
+        }
+        y += SECTION_MARGIN;
 
         // Tools
         y = self.section_label("TOOLS", x, y);
-        for (label, tool, color) in [
-            ("Wall",  Tool::Wall,  OVERLAY),
-            ("Erase", Tool::Erase, OVERLAY),
-            ("Mud",   Tool::Mud,   color_u8!(161, 138, 90, 255)),
-            ("Water", Tool::Water, color_u8!(90, 138, 161, 255)),
-        ] {
+        for (i, (label, tool, color)) in [
+            ("Wall",  Tool::Wall,  self.theme.overlay),
+            ("Erase", Tool::Erase, self.theme.overlay),
+            ("Mud",   Tool::Mud,   self.theme.mud),
+            ("Water", Tool::Water, self.theme.water),
+            ("Select", Tool::Select, self.theme.yellow),
+            ("Mask",  Tool::Mask,  self.theme.mauve),
+        ].into_iter().enumerate() {
+            let bx = x + (i % 2) as f32 * (half_w + BTN_MARGIN);
+            let by = y + (i / 2) as f32 * (BTN_H + BTN_MARGIN);
             let active = state.active_tool == tool;
-            if self.button(label, x, y, w, active, color) {
+            if self.button(label, bx, by, half_w, active, color) {
                 state.active_tool = tool;
             }
-            y += BTN_H + BTN_MARGIN;
         }
-        y += SECTION_MARGIN;
+        y += 3.0 * (BTN_H + BTN_MARGIN);
+        for (i, shape) in Shape::ALL.into_iter().enumerate() {
+            let bx = x + (i % 2) as f32 * (half_w + BTN_MARGIN);
+            let by = y + (i / 2) as f32 * (BTN_H + BTN_MARGIN);
+            if self.button(shape.name(), bx, by, half_w, state.brush.shape == shape, self.theme.accent) {
+                state.brush.shape = shape;
+            }
+        }
+        y += Shape::ALL.len().div_ceil(2) as f32 * (BTN_H + BTN_MARGIN);
+        let label = if state.brush.filled { "Rect: filled" } else { "Rect: outline" };
+        if self.button(label, x, y, w, state.brush.filled, self.theme.accent) {
+            state.brush.filled = !state.brush.filled;
+        }
+        y += BTN_H + BTN_MARGIN;
+        let step = self.selector(&format!("Brush radius {}", state.brush.radius), x, y, w);
+        state.brush.radius = (state.brush.radius as i32 + step).clamp(0, MAX_RADIUS as i32) as usize;
+        y += BTN_H + SECTION_MARGIN;
 
         // Controls
         y = self.section_label("CONTROLS", x, y);
-        if self.button("Reset Search", x, y, w, false, ACCENT) {
+        if self.button("Clear", x, y, half_w, false, self.theme.accent) {
             state.reset_search();
         }
-        y += BTN_H + BTN_MARGIN;
-        if self.button("Reset All (R)", x, y, w, false, RED) {
+        if self.button("Reset (R)", x + half_w + BTN_MARGIN, y, half_w, false, self.theme.red) {
             state.reset_all();
         }
-        y += BTN_H + BTN_MARGIN + SECTION_MARGIN;
+        y += BTN_H + BTN_MARGIN;
+        if self.button("Copy link", x, y, half_w, false, self.theme.accent) {
+            state.copy_link();
+        }
+        if self.button("Paste link", x + half_w + BTN_MARGIN, y, half_w, false, self.theme.accent) {
+            state.paste_link();
+        }
+        y += BTN_H + BTN_MARGIN;
+        if !state.link_report.is_empty() {
+            draw_text(&state.link_report, x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+            y += TEXT_SIZE + BTN_MARGIN;
+        }
+        y += SECTION_MARGIN;
 
         // Stats
         y = self.section_label("STATS", x, y);
-        draw_text(&format!("Nodes: {}", state.stats.nodes_explored), x, y + TEXT_SIZE, TEXT_SIZE, SUBTEXT);
+        draw_text(&format!("Nodes: {}", state.stats.nodes_explored), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
         y += TEXT_SIZE + BTN_MARGIN;
         let path_str = if state.stats.path_found {
             format!("Path: {}", state.stats.path_length)
         } else {
             "Path: -".to_string()
         };
-        draw_text(&path_str, x, y + TEXT_SIZE, TEXT_SIZE, SUBTEXT);
+        draw_text(&path_str, x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
         y += TEXT_SIZE + BTN_MARGIN;
-        draw_text(&format!("Time: {:.2}ms", state.stats.execution_ms), x, y + TEXT_SIZE, TEXT_SIZE, YELLOW);
+        if state.stats.path_found {
+            draw_text(&format!("Cost: {}", state.stats.path_cost), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.green);
+            y += TEXT_SIZE + BTN_MARGIN;
+            // Steps per terrain, each in its own colour.
+            let mut tx = x;
+            for (steps, color) in state.stats.terrain_steps.iter().zip([self.theme.subtext, self.theme.mud, self.theme.water]) {
+                let text = format!("{steps} ");
+                draw_text(&text, tx, y + TEXT_SIZE, TEXT_SIZE, color);
+                tx += measure_text(&text, None, TEXT_SIZE as u16, 1.0).width;
+            }
+            draw_text("norm/mud/water", tx, y + TEXT_SIZE, TEXT_SIZE - 2.0, self.theme.subtext);
+            y += TEXT_SIZE + BTN_MARGIN;
+        }
+        if state.stats.max_open > 0 {
+            draw_text(&format!("Open max: {}", state.stats.max_open), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+            y += TEXT_SIZE + BTN_MARGIN;
+        }
+        if state.stats.peak_bytes > 0 {
+            draw_text(&format!("Memory: {:.1} KB", state.stats.peak_bytes as f64 / 1024.0), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+            y += TEXT_SIZE + BTN_MARGIN;
+        }
+        draw_text(&format!("Time: {:.2}ms", state.stats.execution_ms), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.yellow);
+        y += TEXT_SIZE + BTN_MARGIN;
+        if state.stats.steps > 0 {
+            draw_text(&format!("Steps: {}", state.stats.steps), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+            y += TEXT_SIZE + BTN_MARGIN;
+        }
+        if state.stats.looped {
+            draw_text("Stuck circling a loop", x, y + TEXT_SIZE, TEXT_SIZE, self.theme.red);
+            y += TEXT_SIZE + BTN_MARGIN;
+        }
+        if state.stats.preprocess_ms > 0.0 {
+            draw_text(&format!("Prep: {:.2}ms", state.stats.preprocess_ms), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.yellow);
+            y += TEXT_SIZE + BTN_MARGIN;
+        }
+        draw_text(&format!("Grid: {}x{} (L)", state.grid.width, state.grid.height), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+        if let Some(playback) = &state.maze_playback {
+            y += TEXT_SIZE + BTN_MARGIN;
+            let (done, total) = playback.progress();
+            let label = if playback.paused { "Paused" }
+                        else if state.last_algo.is_some() { "Walking" }
+                        else { "Carving" };
+            draw_text(&format!("{label}: {done}/{total}"), x, y + TEXT_SIZE, TEXT_SIZE, self.theme.accent);
+            y += TEXT_SIZE + BTN_MARGIN;
+            draw_text("Space pause, Enter skip", x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+        }
+        y += TEXT_SIZE + SECTION_MARGIN;
+
+        // Report
+        y = self.section_label("REPORT", x, y);
+        if self.button("Analyze", x, y, w, false, self.theme.accent) {
+            state.report = Some(analysis::analyze(&state.grid));
+        }
+        y += BTN_H + BTN_MARGIN;
+        if let Some(report) = &state.report {
+            let solution = report.solution.map_or("-".to_string(), |steps| steps.to_string());
+            for line in [
+                format!("Open cells: {}", report.open_cells),
+                format!("Dead ends: {}", report.dead_ends),
+                format!("Junctions: {} x3, {} x4", report.degrees[3], report.degrees[4]),
+                format!("Diameter: {}", report.diameter),
+                format!("Solution: {solution}"),
+                format!("Avg corridor: {:.1}", report.avg_corridor),
+                format!("River: {:.0}%", report.river * 100.0),
+            ] {
+                draw_text(&line, x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+                y += TEXT_SIZE + BTN_MARGIN;
+            }
+        }
+        y += SECTION_MARGIN;
+
+        // Theme
+        y = self.section_label("THEME", x, y);
+        let step = self.selector(&state.theme().name, x, y, w);
+        if step != 0 {
+            let count = state.themes.len() as i32;
+            state.theme = (state.theme as i32 + step).rem_euclid(count) as usize;
+        }
+        y += BTN_H + BTN_MARGIN;
+        if self.button("Reload theme files", x, y, w, false, self.theme.accent) {
+            state.reload_themes();
+        }
+        y += BTN_H + BTN_MARGIN;
+        if !state.theme_report.is_empty() {
+            for line in wrap(&state.theme_report, w) {
+                draw_text(&line, x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+                y += TEXT_SIZE + BTN_MARGIN;
+            }
+        }
+        y += SECTION_MARGIN;
+
+        let content_h = y + self.scroll;
+        self.scroll = self.scroll.clamp(0.0, (content_h - screen_h).max(0.0));
+    }
+
+    /// Colour ramp with the value range of the last search underneath.
+    fn legend(&self, state: &AppState, x: f32, mut y: f32, w: f32) -> f32 {
+        const STEPS: usize = 24;
+        let step_w = w / STEPS as f32;
+        for i in 0..STEPS {
+            let color = heatmap::ramp(&self.theme.ramp, i as f32 / (STEPS - 1) as f32);
+            draw_rectangle(x + i as f32 * step_w, y, step_w + 0.5, 10.0, color);
+        }
+        y += 10.0 + BTN_MARGIN;
+
+        let (low, high) = match (state.panes.is_empty(), state.heatmap.range(&state.search)) {
+            (false, _)             => ("low".to_string(), "high (per pane)".to_string()),
+            (true, Some((lo, hi))) => (lo.to_string(), hi.to_string()),
+            (true, None)           => ("no values".to_string(), String::new()),
+        };
+        draw_text(&low, x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+        let high_w = measure_text(&high, None, TEXT_SIZE as u16, 1.0).width;
+        draw_text(&high, x + w - high_w, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+        y + TEXT_SIZE + BTN_MARGIN
+    }
+
+    /// Algorithm picker per pane, a restart button and the stats side by side.
+    fn compare_table(&mut self, state: &mut AppState, x: f32, mut y: f32, w: f32) -> f32 {
+        y = self.section_label("COMPARE (Esc closes)", x, y);
+        for i in 0..state.panes.len() {
+            let step = self.selector(state.panes[i].algo, x, y, w);
+            if step != 0 {
+                let names = algorithms::NAMES;
+                let current = names.iter().position(|&n| n == state.panes[i].algo).unwrap_or(0);
+                state.set_pane_algo(i, names[(current as i32 + step).rem_euclid(names.len() as i32) as usize]);
+            }
+            y += BTN_H + BTN_MARGIN;
+        }
+        if self.button("Run All", x, y, w, false, self.theme.accent) {
+            state.set_panes(state.panes.len());
+        }
+        y += BTN_H + BTN_MARGIN;
+
+        let columns = [x, x + 62.0, x + 108.0, x + 144.0];
+        for (text, cx) in ["Algo", "Nodes", "Cost", "ms"].into_iter().zip(columns) {
+            draw_text(text, cx, y + TEXT_SIZE, TEXT_SIZE, self.theme.text);
+        }
+        y += TEXT_SIZE + BTN_MARGIN;
+        for pane in &state.panes {
+            let stats = &pane.stats;
+            let path = if stats.path_found { stats.path_cost.to_string() } else { "-".to_string() };
+            let cells = [
+                pane.algo.to_string(),
+                stats.nodes_explored.to_string(),
+                path,
+                format!("{:.1}", stats.execution_ms),
+            ];
+            for (text, cx) in cells.iter().zip(columns) {
+                draw_text(text, cx, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+            }
+            y += TEXT_SIZE + BTN_MARGIN;
+        }
+        y + SECTION_MARGIN
+    }
+
+    fn wfc_options(&mut self, state: &mut AppState, x: f32, mut y: f32, half_w: f32) -> f32 {
+        let right = x + half_w + BTN_MARGIN;
+        let can_take = state.selection.is_some();
+        if self.button("Take Sample", x, y, half_w, false, if can_take { self.theme.yellow } else { self.theme.overlay }) {
+            if let Some((a, b)) = state.selection {
+                state.maze_settings.wfc.sample = Some(Sample::from_region(&state.grid, a, b));
+            }
+        }
+        let wfc = &mut state.maze_settings.wfc;
+        if self.button(&format!("N {}", wfc.n), right, y, half_w, false, self.theme.overlay) {
+            wfc.n = if wfc.n >= 4 { 2 } else { wfc.n + 1 };
+        }
+        y += BTN_H + BTN_MARGIN;
+        if self.button(&format!("Seed {}", wfc.seed), x, y, half_w, false, self.theme.overlay) {
+            wfc.seed = macroquad::rand::rand() % 100_000;
+        }
+        if self.button(&format!("Tries {}", wfc.retries), right, y, half_w, false, self.theme.overlay) {
+            wfc.retries = match wfc.retries {
+                0..=1   => 5,
+                2..=5   => 10,
+                6..=10  => 25,
+                11..=25 => 50,
+                _       => 1,
+            };
+        }
+        y += BTN_H + BTN_MARGIN;
+        let sample = match &wfc.sample {
+            Some(sample) => format!("Sample {}x{}", sample.width, sample.height),
+            None => "No sample".to_string(),
+        };
+        draw_text(&sample, x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+        y += TEXT_SIZE + BTN_MARGIN;
+        draw_text(&wfc.report, x, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+        y + TEXT_SIZE + BTN_MARGIN
+    }
+
+    fn bsp_options(&mut self, state: &mut AppState, x: f32, y: f32, half_w: f32) -> f32 {
+        let bsp = &mut state.maze_settings.bsp;
+
+        if self.button(&format!("Leaf {}", bsp.min_leaf), x, y, half_w, false, self.theme.overlay) {
+            bsp.min_leaf = if bsp.min_leaf >= 20 { 6 } else { bsp.min_leaf + 2 };
+        }
+        let label = if bsp.far_endpoints { "Ends: far" } else { "Ends: first" };
+        if self.button(label, x + half_w + BTN_MARGIN, y, half_w, bsp.far_endpoints, self.theme.overlay) {
+            bsp.far_endpoints = !bsp.far_endpoints;
+        }
+        y + BTN_H + BTN_MARGIN
+    }
+
+    fn noise_options(&mut self, state: &mut AppState, x: f32, mut y: f32, half_w: f32) -> f32 {
+        let noise = &mut state.maze_settings.noise;
+        let right = x + half_w + BTN_MARGIN;
+
+        if self.button(&format!("Seed {}", noise.seed), x, y, half_w, false, self.theme.overlay) {
+            noise.seed = macroquad::rand::rand() % 100_000;
+        }
+        if self.button(&format!("Scale {}", noise.scale), right, y, half_w, false, self.theme.overlay) {
+            noise.scale = if noise.scale >= 64.0 { 4.0 } else { noise.scale * 2.0 };
+        }
+        y += BTN_H + BTN_MARGIN;
+        if self.button(&format!("Water <{}", percent(noise.water)), x, y, half_w, false, self.theme.water) {
+            noise.water = next_threshold(noise.water, 0.0, noise.mud);
+        }
+        if self.button(&format!("Mud <{}", percent(noise.mud)), right, y, half_w, false, self.theme.mud) {
+            noise.mud = next_threshold(noise.mud, noise.water, noise.wall);
+        }
+        y += BTN_H + BTN_MARGIN;
+        if self.button(&format!("Wall >{}", percent(noise.wall)), x, y, half_w, false, self.theme.overlay) {
+            noise.wall = next_threshold(noise.wall, noise.mud, 1.0);
+        }
+        if self.button(&format!("Octaves {}", noise.octaves), right, y, half_w, false, self.theme.overlay) {
+            noise.octaves = noise.octaves % 6 + 1;
+        }
+        y + BTN_H + BTN_MARGIN
+    }
+
+    fn cave_options(&mut self, state: &mut AppState, x: f32, mut y: f32, half_w: f32) -> f32 {
+        let cave = &mut state.maze_settings.cave;
+        let right = x + half_w + BTN_MARGIN;
+
+        if self.button(&format!("Fill {}%", percent(cave.fill)), x, y, half_w, false, self.theme.overlay) {
+            let fill = percent(cave.fill);
+            cave.fill = if fill >= 60 { 0.35 } else { (fill + 5) as f32 / 100.0 };
+        }
+        if self.button(&format!("Steps {}", cave.iterations), right, y, half_w, false, self.theme.overlay) {
+            cave.iterations = (cave.iterations + 1) % 11;
+        }
+        y += BTN_H + BTN_MARGIN;
+        if self.button(&format!("Birth {}", cave.birth), x, y, half_w, false, self.theme.overlay) {
+            cave.birth = if cave.birth >= 8 { 3 } else { cave.birth + 1 };
+        }
+        if self.button(&format!("Survive {}", cave.survival), right, y, half_w, false, self.theme.overlay) {
+            cave.survival = if cave.survival >= 8 { 2 } else { cave.survival + 1 };
+        }
+        y += BTN_H + BTN_MARGIN;
+        let label = if cave.connect_pockets { "Pockets: tunnel" } else { "Pockets: fill" };
+        if self.button(label, x, y, half_w * 2.0 + BTN_MARGIN, false, self.theme.overlay) {
+            cave.connect_pockets = !cave.connect_pockets;
+        }
+        y + BTN_H + BTN_MARGIN
+    }
+
+    /// The drop-down console over the top of the grid area.
+    pub fn console(&self, console: &Console, grid_w: f32, screen_h: f32) {
+        let line_h = TEXT_SIZE + 4.0;
+        let height = (screen_h * 0.4).max(line_h * 6.0);
+        draw_rectangle(0.0, 0.0, grid_w, height, Color { a: 0.94, ..self.theme.bg });
+        draw_line(0.0, height, grid_w, height, 1.0, self.theme.accent);
+
+        let mut y = height - 8.0;
+        let cursor = if get_time().fract() < 0.5 { "_" } else { "" };
+        draw_text(&format!("> {}{cursor}", console.input), 8.0, y, TEXT_SIZE, self.theme.text);
+        y -= line_h;
+        if console.lines.is_empty() {
+            draw_text("Type help for commands; Tab completes, Up/Down recall, ` or Esc closes", 8.0, y, TEXT_SIZE, self.theme.subtext);
+            return;
+        }
+
+        // Newest at the bottom; long lines wrap, short ones keep their spacing.
+        let width = grid_w - 16.0;
+        'lines: for (kind, line) in console.lines.iter().rev() {
+            let color = match kind {
+                console::Kind::Input  => self.theme.subtext,
+                console::Kind::Output => self.theme.text,
+                console::Kind::Error  => self.theme.red,
+            };
+            let rows = if measure_text(line, None, TEXT_SIZE as u16, 1.0).width > width { wrap(line, width) } else { vec![line.clone()] };
+            for row in rows.iter().rev() {
+                if y < TEXT_SIZE { break 'lines; }
+                draw_text(row, 8.0, y, TEXT_SIZE, color);
+                y -= line_h;
+            }
+        }
+    }
+
+    /// Tooltip for the cell under the mouse: what it is, and what the last
+    /// search made of it. Drawn after the grid so it sits on top.
+    pub fn inspector(&self, state: &AppState, grid_w: f32, screen_h: f32) {
+        let (mx, my) = mouse_position();
+        if mx < 0.0 || my < 0.0 || mx >= grid_w || my >= screen_h { return; }
+        if is_mouse_button_down(MouseButton::Left) || is_mouse_button_down(MouseButton::Right) { return; }
+
+        // In the split view, inspect the pane under the mouse.
+        let (grid, search, playback, idx) = match state.panes.len() {
+            0 => {
+                let Some(idx) = self.hovered else { return };
+                (&state.grid, &state.search, &state.maze_playback, idx)
+            }
+            count => {
+                let Some((i, (x, y, w, h))) = (0..count)
+                    .map(|i| (i, compare::layout(i, count, grid_w, screen_h)))
+                    .find(|&(_, (x, y, w, h))| mx >= x && mx < x + w && my >= y && my < y + h)
+                else { return };
+                let pane = &state.panes[i];
+                let col = ((mx - x) / (w / pane.grid.width as f32)) as usize;
+                let row = ((my - y) / (h / pane.grid.height as f32)) as usize;
+                if row >= pane.grid.height || col >= pane.grid.width { return; }
+                (&pane.grid, &pane.search, &pane.playback, pane.grid.idx(row, col))
+            }
+        };
+        let (row, col) = (idx / grid.width, idx % grid.width);
+        let cell = grid.cell(idx);
+
+        let mut lines = vec![format!("({row}, {col})")];
+        let mut flags: Vec<&str> = Vec::new();
+        if cell.is_wall  { flags.push("wall"); }
+        if cell.is_start { flags.push("start"); }
+        if cell.is_end   { flags.push("end"); }
+        if !cell.is_wall {
+            let terrain = match cell.cost {
+                COST_MUD   => "mud",
+                COST_WATER => "water",
+                _          => "normal",
+            };
+            lines.push(format!("Cost: {} ({terrain})", cell.cost));
+        }
+        if !flags.is_empty() { lines.push(flags.join(", ")); }
+
+        // While a run replays, only show what the replay has reached.
+        let reached = playback.is_none() || cell.is_visited || cell.is_start;
+        let known = |values: &[u32]| values.get(idx).copied().filter(|&v| v != u32::MAX);
+        if reached && (known(&search.g).is_some() || known(&search.discovered).is_some()) {
+            let show = |v: Option<u32>| v.map_or("-".to_string(), |v| v.to_string());
+            let f = known(&search.h).and(known(&search.g)).map(|_| search.f(idx));
+            lines.push(format!("g {}  h {}  f {}", show(known(&search.g)), show(known(&search.h)), show(f)));
+            if let Some(&parent) = search.parent.get(idx).filter(|&&p| p != usize::MAX) {
+                lines.push(format!("Parent: ({}, {})", parent / grid.width, parent % grid.width));
+            }
+            lines.push(format!("Found: {}  Expanded: {}", show(known(&search.discovered)), show(known(&search.order))));
+        }
+
+        let line_h = TEXT_SIZE + 4.0;
+        let box_w = lines.iter().map(|l| measure_text(l, None, TEXT_SIZE as u16, 1.0).width).fold(0.0, f32::max) + 16.0;
+        let box_h = lines.len() as f32 * line_h + 10.0;
+        // Beside the cursor, flipped to the other side near the edges.
+        let bx = if mx + 16.0 + box_w > grid_w { mx - 12.0 - box_w } else { mx + 16.0 };
+        let by = if my + 16.0 + box_h > screen_h { my - 12.0 - box_h } else { my + 16.0 };
+        draw_rectangle(bx, by, box_w, box_h, self.theme.bg);
+        draw_rectangle_lines(bx, by, box_w, box_h, 1.0, self.theme.overlay);
+        for (i, line) in lines.iter().enumerate() {
+            let color = if i == 0 { self.theme.accent } else { self.theme.text };
+            draw_text(line, bx + 8.0, by + 5.0 + (i as f32 + 1.0) * line_h - 5.0, TEXT_SIZE, color);
+        }
+    }
+
+    /// Pseudocode of the last solver in the grid's corner, with the line the
+    /// replay is on highlighted and the values it is working with.
+    pub fn pseudocode(&self, state: &AppState, screen_h: f32) {
+        let Some(listing) = state.last_algo.and_then(pseudocode::listing) else { return };
+        let playback = state.maze_playback.as_ref();
+        let current_line = playback.and_then(|p| p.last).and_then(|event| listing.line(event));
+        let line_h = TEXT_SIZE + 4.0;
+        let text_w = |text: &str| measure_text(text, None, TEXT_SIZE as u16, 1.0).width;
+        let code_w = listing.lines.iter().map(|line| text_w(line)).fold(0.0, f32::max);
+        let panel_w = code_w.max(300.0) + 16.0;
+
+        let vars = match playback {
+            Some(playback) => {
+                let grid = &state.grid;
+                let mut vars = Vec::new();
+                if let Some(head) = playback.head {
+                    vars.push(format!("current ({}, {})", head / grid.width, head % grid.width));
+                    let g = state.search.g.get(head).copied().filter(|&g| g != u32::MAX);
+                    vars.push(format!("cost {}", g.map_or("-".to_string(), |g| g.to_string())));
+                }
+                if let Some(name) = listing.frontier {
+                    let size = if algorithms::is_walker(state.last_algo.unwrap_or_default()) {
+                        playback.marked.iter().filter(|&&m| m).count()
+                    } else {
+                        (0..grid.width * grid.height).filter(|&i| grid.cell(i).is_open).count()
+                    };
+                    vars.push(format!("{name} {size}"));
+                }
+                vars.join("   ")
+            }
+            None => "Animate a run to follow it line by line".to_string(),
+        };
+        let guide = listing.guide();
+        // The guide is markdown; drop the emphasis and maths markers.
+        let intro = guide
+            .map(|(_, intro)| wrap(&intro.replace("**", "").replace('$', ""), panel_w - 16.0))
+            .unwrap_or_default();
+
+        let rows = listing.lines.len() + 2 + intro.len() + usize::from(!intro.is_empty());
+        let panel_h = rows as f32 * line_h + 12.0;
+        let (px, py) = (10.0, screen_h - panel_h - 10.0);
+        draw_rectangle(px, py, panel_w, panel_h, Color { a: 0.92, ..self.theme.bg });
+        draw_rectangle_lines(px, py, panel_w, panel_h, 1.0, self.theme.overlay);
+
+        let mut y = py + 6.0;
+        let title = match guide {
+            Some((logic, _)) => format!("{}  {logic}", state.last_algo.unwrap_or_default()),
+            None             => state.last_algo.unwrap_or_default().to_string(),
+        };
+        draw_text(&title, px + 8.0, y + TEXT_SIZE, TEXT_SIZE, self.theme.accent);
+        y += line_h;
+        for (i, line) in listing.lines.iter().enumerate() {
+            let active = current_line == Some(i);
+            if active {
+                draw_rectangle(px + 4.0, y + 1.0, panel_w - 8.0, line_h, self.theme.overlay);
+            }
+            draw_text(line, px + 8.0, y + TEXT_SIZE, TEXT_SIZE, if active { self.theme.yellow } else { self.theme.text });
+            y += line_h;
+        }
+        draw_text(&vars, px + 8.0, y + TEXT_SIZE, TEXT_SIZE, self.theme.peach);
+        y += line_h * 2.0;
+        for line in &intro {
+            draw_text(line, px + 8.0, y + TEXT_SIZE, TEXT_SIZE, self.theme.subtext);
+            y += line_h;
+        }
     }
 
     fn section_label(&self, label: &str, x: f32, y: f32) -> f32 {
-        draw_text(label, x, y + TEXT_SIZE, TEXT_SIZE - 2.0, SUBTEXT);
+        draw_text(label, x, y + TEXT_SIZE, TEXT_SIZE - 2.0, self.theme.subtext);
         y + TEXT_SIZE + BTN_MARGIN
     }
 
+    /// `< label >` row for cycling through a list. Returns -1, 0 or 1.
+    fn selector(&mut self, label: &str, x: f32, y: f32, w: f32) -> i32 {
+        let mut step = 0;
+        if self.button("<", x, y, BTN_H, false, self.theme.accent) { step = -1; }
+        if self.button(">", x + w - BTN_H, y, BTN_H, false, self.theme.accent) { step = 1; }
+        let text_w = measure_text(label, None, TEXT_SIZE as u16, 1.0).width;
+        draw_text(label, x + (w - text_w) / 2.0, y + BTN_H / 2.0 + TEXT_SIZE / 2.0 - 2.0, TEXT_SIZE, self.theme.text);
+        step
+    }
+
     fn button(&mut self, label: &str, x: f32, y: f32, w: f32, active: bool, color: Color) -> bool {
         let (mx, my) = mouse_position();
         let hovered = mx >= x && mx <= x + w && my >= y && my <= y + BTN_H;
         let clicked = hovered && is_mouse_button_pressed(MouseButton::Left);
 
         let bg = if active       { color }
-                 else if hovered { OVERLAY }
-                 else            { SURFACE };
+                 else if hovered { self.theme.overlay }
+                 else            { self.theme.surface };
 
         draw_rectangle(x, y, w, BTN_H, bg);
-        draw_rectangle_lines(x, y, w, BTN_H, 1.0, if hovered || active { color } else { OVERLAY });
-        draw_text(label, x + 8.0, y + BTN_H / 2.0 + TEXT_SIZE / 2.0 - 2.0, TEXT_SIZE, if active { SURFACE } else { TEXT });
+        draw_rectangle_lines(x, y, w, BTN_H, 1.0, if hovered || active { color } else { self.theme.overlay });
+        draw_text(label, x + 8.0, y + BTN_H / 2.0 + TEXT_SIZE / 2.0 - 2.0, TEXT_SIZE, if active { self.theme.surface } else { self.theme.text });
 
         clicked
     }
 }
 
-use crate::maze;
\ No newline at end of file
+/// Breaks `text` into lines no wider than `width` at `TEXT_SIZE`.
+fn wrap(text: &str, width: f32) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let candidate = if line.is_empty() { word.to_string() } else { format!("{line} {word}") };
+        if !line.is_empty() && measure_text(&candidate, None, TEXT_SIZE as u16, 1.0).width > width {
+            lines.push(std::mem::replace(&mut line, word.to_string()));
+        } else {
+            line = candidate;
+        }
+    }
+    if !line.is_empty() { lines.push(line); }
+    lines
+}
+
+fn percent(fraction: f32) -> u32 {
+    (fraction * 100.0).round() as u32
+}
+
+fn next_level(current: f32) -> f32 {
+    let i = LEVELS.iter().position(|&l| l == current).unwrap_or(0);
+    LEVELS[(i + 1) % LEVELS.len()]
+}
+
+/// Steps a threshold by 5% and wraps within `low..=high`, so bands keep their order.
+fn next_threshold(current: f32, low: f32, high: f32) -> f32 {
+    let next = (percent(current) + 5) as f32 / 100.0;
+    if next > high { low } else { next }
+}
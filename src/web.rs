@@ -0,0 +1,26 @@
+// src/web.rs
+use js_sys::wasm_bindgen::{JsCast, JsValue};
+use js_sys::{Array, Function, Object, Reflect};
+
+// Browser access for the `web` feature. macroquad owns the page's JS glue,
+// so everything goes through reflection on the global object.
+
+/// `globalThis.<name>`, e.g. `localStorage` or `location`.
+pub fn global(name: &str) -> Option<Object> {
+    get(&js_sys::global(), name)?.dyn_into().ok()
+}
+
+pub fn get(target: &Object, name: &str) -> Option<JsValue> {
+    Reflect::get(target, &JsValue::from_str(name)).ok()
+}
+
+/// `target.method(...args)`.
+pub fn call(target: &Object, method: &str, args: &[JsValue]) -> Option<JsValue> {
+    let function: Function = get(target, method)?.dyn_into().ok()?;
+    function.apply(target, &args.iter().collect::<Array>()).ok()
+}
+
+/// `target.<name> = value`.
+pub fn set(target: &Object, name: &str, value: &JsValue) {
+    let _ = Reflect::set(target, &JsValue::from_str(name), value);
+}